@@ -0,0 +1,45 @@
+//! Prints a drawing of a puzzle input, e.g.
+//! `cargo run --example render -- day17 input/2023/day17.txt` or
+//! `cargo run --example render -- day23 input/2023/day23.txt | dot -Tsvg > day23.svg`
+use aoc2023::render;
+use std::{env, fs, process};
+
+const USAGE: &str =
+    "usage: render <day8|day17|day17-ultra|day20|day23|day23-no-slopes> <input file>";
+
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let [drawing, path] = &args[..] else {
+        eprintln!("{USAGE}");
+        process::exit(2);
+    };
+    let input = fs::read_to_string(path).unwrap_or_else(|err| {
+        eprintln!("{path}: {err}");
+        process::exit(1);
+    });
+    let input = input.trim_end();
+
+    let output = match drawing.as_str() {
+        "day8" => render::day8_dot(input).map(Some),
+        "day17" => render::day17_route(input, false),
+        "day17-ultra" => render::day17_route(input, true),
+        "day20" => render::day20_dot(input).map(Some),
+        "day23" => render::day23_dot(input, true).map(Some),
+        "day23-no-slopes" => render::day23_dot(input, false).map(Some),
+        _ => {
+            eprintln!("{USAGE}");
+            process::exit(2);
+        }
+    };
+    match output {
+        Ok(Some(drawing)) => print!("{drawing}"),
+        Ok(None) => {
+            eprintln!("nothing to draw");
+            process::exit(1);
+        }
+        Err(err) => {
+            eprintln!("{err}");
+            process::exit(1);
+        }
+    }
+}
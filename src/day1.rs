@@ -37,3 +37,31 @@ fn part2(input: &str) -> u32 {
         .map(|v| 10 * v.first().unwrap() + v.last().unwrap())
         .sum()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE1: &str = "1abc2
+pqr3stu8vwx
+a1b2c3d4e5f
+treb7uchet";
+
+    const EXAMPLE2: &str = "two1nine
+eightwothree
+abcone2threexyz
+xtwone3four
+4nineeightseven2
+zoneight234
+7pqrstsixteen";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(part1(EXAMPLE1), 142);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(part2(EXAMPLE2), 281);
+    }
+}
@@ -1,5 +1,7 @@
-use crate::day10::Direction::{E, N, S, W};
-use itertools::Itertools;
+use crate::util::error::*;
+use crate::util::geometry;
+use crate::util::grid::*;
+use crate::util::point::*;
 
 #[derive(Debug, Copy, Clone, PartialEq, Default)]
 struct Cell {
@@ -8,7 +10,6 @@ struct Cell {
     south: bool,
     west: bool,
 }
-static START_POS: (usize, usize) = (128, 36);
 
 impl Cell {
     fn from_char(c: char) -> Self {
@@ -38,73 +39,190 @@ impl Cell {
                 west: true,
                 ..Default::default()
             },
-            'F' | 'S' => Self {
+            'F' => Self {
                 south: true,
                 east: true,
                 ..Default::default()
             },
-            '.' => Cell::default(),
+            '.' | 'S' => Cell::default(),
             _ => unreachable!(),
         }
     }
+
+    fn connects(&self, dir: Point) -> bool {
+        match dir {
+            UP => self.north,
+            RIGHT => self.east,
+            DOWN => self.south,
+            LEFT => self.west,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PipeMaze {
+    grid: Grid<Cell>,
+    start: Point,
+    path: Vec<Point>,
+}
+
+// the order a pipe's connections are tried in when following the loop
+const EXITS: [Point; 4] = [RIGHT, DOWN, LEFT, UP];
+
+fn error_at(pos: Point, message: impl Into<String>) -> ParseError {
+    ParseError::new(10, pos.y as usize + 1, pos.x as usize + 1, message)
 }
 
-fn get_path(grid: &[Vec<Cell>]) -> Vec<(usize, usize)> {
-    let mut path = vec![START_POS];
-    let mut came_from = W;
-    let mut row = START_POS.0;
-    let mut col = START_POS.1 + 1;
-    while (row, col) != START_POS {
-        path.push((row, col));
-        if came_from != E && grid[row][col].east {
-            col += 1;
-            came_from = W;
-        } else if came_from != S && grid[row][col].south {
-            row += 1;
-            came_from = N;
-        } else if came_from != W && grid[row][col].west {
-            col -= 1;
-            came_from = E;
-        } else if came_from != N && grid[row][col].north {
-            row -= 1;
-            came_from = S;
+impl PipeMaze {
+    fn new(grid: Grid<Cell>, start: Point) -> Result<Self, ParseError> {
+        let mut maze = Self {
+            grid,
+            start,
+            path: Vec::new(),
+        };
+        maze.path = maze.trace_loop()?;
+        Ok(maze)
+    }
+
+    fn trace_loop(&self) -> Result<Vec<Point>, ParseError> {
+        let grid = &self.grid;
+        let mut heading = EXITS
+            .into_iter()
+            .find(|&dir| grid[self.start].connects(dir))
+            .expect("start tile connects to two pipes");
+        let mut pos = self.start + heading;
+        let mut path = vec![self.start];
+        while pos != self.start {
+            let Some(cell) = grid.get(pos) else {
+                return Err(error_at(path[path.len() - 1], "loop leaves the grid"));
+            };
+            path.push(pos);
+            heading = EXITS
+                .into_iter()
+                .find(|&dir| dir != -heading && cell.connects(dir))
+                .ok_or_else(|| error_at(pos, "loop is broken"))?;
+            pos += heading;
         }
+        Ok(path)
     }
-    path
+}
+
+fn infer_start(grid: &Grid<Cell>, start: Point) -> Result<Cell, ParseError> {
+    let connects = |dir: Point| {
+        grid.get(start + dir)
+            .is_some_and(|cell| cell.connects(-dir))
+    };
+    let cell = Cell {
+        north: connects(UP),
+        east: connects(RIGHT),
+        south: connects(DOWN),
+        west: connects(LEFT),
+    };
+    let connections = EXITS.into_iter().filter(|&dir| cell.connects(dir)).count();
+    if connections != 2 {
+        let message = format!("start tile connects to {connections} pipes instead of two");
+        return Err(error_at(start, message));
+    }
+    Ok(cell)
 }
 
 #[aoc_generator(day10)]
-fn parse_input(input: &str) -> Vec<Vec<Cell>> {
-    input
-        .lines()
-        .map(|line| line.chars().map(Cell::from_char).collect())
-        .collect()
+fn parse_input(input: &str) -> Result<PipeMaze, ParseError> {
+    validate_grid(10, input, input, "|-LJ7F.S")?;
+    let start = Grid::parse(input)
+        .find(b'S')
+        .ok_or_else(|| ParseError::new(10, 1, 1, "no start tile"))?;
+    let mut grid = Grid::parse_with(input, |b| Cell::from_char(b as char));
+    grid[start] = infer_start(&grid, start)?;
+
+    PipeMaze::new(grid, start)
 }
 
 #[aoc(day10, part1)]
-fn part1(input: &[Vec<Cell>]) -> usize {
-    get_path(input).len() / 2
-}
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
-enum Direction {
-    N,
-    S,
-    E,
-    W,
+fn part1(input: &PipeMaze) -> usize {
+    input.path.len() / 2
 }
 
 #[aoc(day10, part2)]
-fn part2(input: &[Vec<Cell>]) -> i64 {
-    let mut path = get_path(input);
-    let path_len = path.len() as i64;
-    // add start again for shoelace formula
-    path.push(START_POS);
-    let area = path
-        .into_iter()
-        .map(|(x, y)| (x as i64, y as i64))
-        .tuple_windows()
-        .fold(0, |acc, ((x0, y0), (x1, y1))| acc + ((y0 + y1) * (x0 - x1)))
-        .abs();
-
-    area / 2 - (path_len / 2 - 1)
+fn part2(input: &PipeMaze) -> i64 {
+    let path: Vec<Point<i64>> = input.path.iter().map(|pos| pos.widen()).collect();
+    geometry::interior_lattice_points(&path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIMPLE_LOOP: &str = ".....
+.S-7.
+.|.|.
+.L-J.
+.....";
+
+    const COMPLEX_LOOP: &str = "7-F7-
+.FJ|7
+SJLL7
+|F--J
+LJ.LJ";
+
+    const ENCLOSED: &str = "...........
+.S-------7.
+.|F-----7|.
+.||.....||.
+.||.....||.
+.|L-7.F-J|.
+.|..|.|..|.
+.L--J.L--J.
+...........";
+
+    const LARGER: &str = ".F----7F7F7F7F-7....
+.|F--7||||||||FJ....
+.||.FJ||||||||L7....
+FJL7L7LJLJ||LJ.L-7..
+L--J.L7...LJS7F-7L7.
+....F-J..F7FJ|L7L7L7
+....L7.F7||L7|.L7L7|
+.....|FJLJ|FJ|F7|.LJ
+....FJL-7.||.||||...
+....L---J.LJ.LJLJ...";
+
+    const JUNK: &str = "FF7FSF7F7F7F7F7F---7
+L|LJ||||||||||||F--J
+FL-7LJLJ||||||LJL-77
+F--JF--7||LJLJ7F7FJ-
+L---JF-JLJ.||-FJLJJ7
+|F|F-JF---7F7-L7L|7|
+|FFJF7L7F-JF7|JL---7
+7-L-JL7||F7|L7F-7F7|
+L.L7LFJ|||||FJL7||LJ
+L7JLJL-JLJLJL--JLJ.L";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(part1(&parse_input(SIMPLE_LOOP).unwrap()), 4);
+        assert_eq!(part1(&parse_input(COMPLEX_LOOP).unwrap()), 8);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(part2(&parse_input(ENCLOSED).unwrap()), 4);
+        assert_eq!(part2(&parse_input(LARGER).unwrap()), 8);
+        assert_eq!(part2(&parse_input(JUNK).unwrap()), 10);
+    }
+
+    #[test]
+    fn infers_start_shape() {
+        let maze = parse_input(COMPLEX_LOOP).unwrap();
+        assert_eq!(maze.start, Point::new(0, 2));
+        assert_eq!(maze.grid[maze.start], Cell::from_char('F'));
+    }
+
+    #[test]
+    fn rejects_ambiguous_start() {
+        assert_eq!(
+            parse_input(".|.\n-S-\n.|.").unwrap_err(),
+            ParseError::new(10, 2, 2, "start tile connects to 4 pipes instead of two")
+        );
+    }
 }
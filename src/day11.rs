@@ -1,3 +1,5 @@
+use crate::util::error::*;
+use crate::util::grid::*;
 use itertools::Itertools;
 use std::cmp::{max, min};
 use std::collections::BTreeSet;
@@ -10,30 +12,28 @@ struct Cosmos {
 }
 
 #[aoc_generator(day11)]
-fn parse_input(input: &str) -> Cosmos {
-    let grid: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
-    let empty_rows = (0..grid.len())
-        .filter(|&i| !grid[i].contains(&'#'))
+fn parse_input(input: &str) -> Result<Cosmos, ParseError> {
+    validate_grid(11, input, input, ".#")?;
+    let grid = Grid::parse(input);
+    let empty_rows = (0..grid.height)
+        .filter(|&y| !grid.row(y).contains(&b'#'))
+        .map(|y| y as usize)
         .collect();
-    let empty_cols = (0..grid[0].len())
-        .filter(|&j| !(0..grid.len()).map(|i| grid[i][j]).contains(&'#'))
+    let empty_cols = (0..grid.width)
+        .filter(|&x| !grid.column(x).contains(&b'#'))
+        .map(|x| x as usize)
         .collect();
     let stars = grid
-        .iter()
-        .enumerate()
-        .flat_map(|(i, row)| {
-            row.iter()
-                .enumerate()
-                .filter(|&(_, cell)| *cell == '#')
-                .map(move |(j, _)| (i, j))
-        })
+        .iter_with_points()
+        .filter(|&(_, &cell)| cell == b'#')
+        .map(|(p, _)| (p.y as usize, p.x as usize))
         .collect();
 
-    Cosmos {
+    Ok(Cosmos {
         stars,
         empty_rows,
         empty_cols,
-    }
+    })
 }
 
 fn minmax(a: usize, b: usize) -> (usize, usize) {
@@ -75,3 +75,29 @@ fn part1(input: &Cosmos) -> usize {
 fn part2(input: &Cosmos) -> usize {
     distances(input, true)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "...#......
+.......#..
+#.........
+..........
+......#...
+.#........
+.........#
+..........
+.......#..
+#...#.....";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(part1(&parse_input(EXAMPLE).unwrap()), 374);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(part2(&parse_input(EXAMPLE).unwrap()), 82_000_210);
+    }
+}
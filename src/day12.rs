@@ -1,3 +1,4 @@
+use crate::util::error::*;
 use std::collections::HashMap;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -7,19 +8,25 @@ struct Record {
 }
 
 #[aoc_generator(day12)]
-fn parse_input(input: &str) -> Vec<Record> {
+fn parse_input(input: &str) -> Result<Vec<Record>, ParseError> {
     input
         .lines()
         .map(|line| {
-            let (spring, counts) = line.split_once(' ').unwrap();
+            let (spring, counts) = line
+                .split_once(' ')
+                .ok_or_else(|| ParseError::at(12, input, line, "expected `<springs> <counts>`"))?;
+            validate_grid(12, input, spring, "?.#")?;
             let counts = counts
                 .split(',')
-                .filter_map(|s| s.parse::<usize>().ok())
-                .collect();
-            Record {
+                .map(|s| parse_token(12, input, s))
+                .collect::<Result<Vec<usize>, _>>()?;
+            if counts.is_empty() {
+                return Err(ParseError::at(12, input, line, "no group sizes"));
+            }
+            Ok(Record {
                 spring: spring.to_string(),
                 counts,
-            }
+            })
         })
         .collect()
 }
@@ -84,3 +91,35 @@ fn part2(input: &[Record]) -> u64 {
             acc + solve(&mut HashMap::new(), &record, (0, 0, 0))
         })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "???.### 1,1,3
+.??..??...?##. 1,1,3
+?#?#?#?#?#?#?#? 1,3,1,6
+????.#...#... 4,1,1
+????.######..#####. 1,6,5
+?###???????? 3,2,1";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(part1(&parse_input(EXAMPLE).unwrap()), 21);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(part2(&parse_input(EXAMPLE).unwrap()), 525_152);
+    }
+
+    #[test]
+    fn solve_each_record() {
+        let counts = parse_input(EXAMPLE)
+            .unwrap()
+            .iter()
+            .map(|record| solve(&mut HashMap::new(), record, (0, 0, 0)))
+            .collect::<Vec<_>>();
+        assert_eq!(counts, vec![1, 4, 1, 1, 4, 10]);
+    }
+}
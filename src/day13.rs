@@ -1,3 +1,5 @@
+use crate::util::error::*;
+
 #[derive(Debug)]
 struct Landscape {
     rows: Vec<u32>,
@@ -5,12 +7,21 @@ struct Landscape {
 }
 
 #[aoc_generator(day13)]
-fn parse_input(input: &str) -> Vec<Landscape> {
+fn parse_input(input: &str) -> Result<Vec<Landscape>, ParseError> {
     input
         .split("\n\n")
         .map(|block| {
+            validate_grid(13, input, block, ".#")?;
             let mut rows = vec![0u32; block.lines().count()];
-            let mut cols = vec![0u32; block.find('\n').unwrap()];
+            let mut cols = vec![0u32; block.lines().next().map_or(0, str::len)];
+            if rows.len() > 32 || cols.len() > 32 {
+                return Err(ParseError::at(
+                    13,
+                    input,
+                    block,
+                    "pattern is larger than 32x32",
+                ));
+            }
 
             for (row_ind, row) in block.lines().enumerate() {
                 for (col_ind, c) in row.char_indices() {
@@ -19,7 +30,7 @@ fn parse_input(input: &str) -> Vec<Landscape> {
                     rows[row_ind] = (rows[row_ind] << 1) + val;
                 }
             }
-            Landscape { rows, cols }
+            Ok(Landscape { rows, cols })
         })
         .collect()
 }
@@ -93,3 +104,44 @@ fn part1(input: &[Landscape]) -> usize {
 fn part2(input: &[Landscape]) -> usize {
     input.iter().map(smudge_score).sum()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "#.##..##.
+..#.##.#.
+##......#
+##......#
+..#.##.#.
+..##..##.
+#.##..##.
+
+#...##..#
+#....#..#
+..##..###
+#####.##.
+#####.##.
+..##..###
+#....#..#";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(part1(&parse_input(EXAMPLE).unwrap()), 405);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(part2(&parse_input(EXAMPLE).unwrap()), 400);
+    }
+
+    #[test]
+    fn reflect_smudge_moves_line() {
+        let landscapes = parse_input(EXAMPLE).unwrap();
+        assert_eq!(reflect(&landscapes[0].cols), 5);
+        assert_eq!(reflect_smudge(&landscapes[0].cols), 0);
+        assert_eq!(reflect_smudge(&landscapes[0].rows), 3);
+        assert_eq!(reflect(&landscapes[1].rows), 4);
+        assert_eq!(reflect_smudge(&landscapes[1].rows), 1);
+    }
+}
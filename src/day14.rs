@@ -1,36 +1,36 @@
+use crate::util::error::*;
+use crate::util::grid::*;
+use crate::util::point::*;
 use std::collections::HashMap;
-type Grid = Vec<Vec<char>>;
 #[aoc_generator(day14)]
-fn parse_input(input: &str) -> Grid {
-    input.lines().map(|line| line.chars().collect()).collect()
+fn parse_input(input: &str) -> Result<Grid<u8>, ParseError> {
+    validate_grid(14, input, input, ".#O")?;
+    Ok(Grid::parse(input))
 }
 
-fn load(grid: &Grid) -> usize {
-    (0..grid.len())
-        .map(|r| {
-            (0..grid[0].len())
-                .filter(|&c| grid[r][c] == 'O')
-                .map(|_| grid.len() - r)
-                .sum::<usize>()
-        })
+fn load(grid: &Grid<u8>) -> usize {
+    grid.iter_with_points()
+        .filter(|&(_, &cell)| cell == b'O')
+        .map(|(p, _)| (grid.height - p.y) as usize)
         .sum()
 }
 #[aoc(day14, part1)]
-fn part1(grid: &[Vec<char>]) -> usize {
-    let mut grid = grid.to_vec();
+fn part1(grid: &Grid<u8>) -> usize {
+    let mut grid = grid.clone();
     roll_north(&mut grid);
     load(&grid)
 }
 
-fn roll_north(grid: &mut Grid) {
+fn roll_north(grid: &mut Grid<u8>) {
     let mut done = false;
     while !done {
         done = true;
-        for r in 0..grid.len() - 1 {
-            for c in 0..grid[0].len() {
-                if grid[r + 1][c] == 'O' && grid[r][c] == '.' {
-                    grid[r][c] = 'O';
-                    grid[r + 1][c] = '.';
+        for y in 1..grid.height {
+            for x in 0..grid.width {
+                let p = Point::new(x, y);
+                if grid[p] == b'O' && grid[p + UP] == b'.' {
+                    grid[p + UP] = b'O';
+                    grid[p] = b'.';
                     done = false;
                 }
             }
@@ -38,23 +38,14 @@ fn roll_north(grid: &mut Grid) {
     }
 }
 
-fn rotate_grid(grid: &Grid) -> Grid {
-    let mut new = vec![vec!['.'; grid.len()]; grid[0].len()];
-    for r in 0..grid.len() {
-        for c in 0..grid[0].len() {
-            new[c][grid.len() - r - 1] = grid[r][c];
-        }
-    }
-    new
-}
 #[aoc(day14, part2)]
-fn part2(input: &[Vec<char>]) -> usize {
-    let mut grid = input.to_vec();
+fn part2(input: &Grid<u8>) -> usize {
+    let mut grid = input.clone();
     let mut seen = HashMap::new();
     for i in 1..1_000_000_000 {
         for _ in 0..4 {
             roll_north(&mut grid);
-            grid = rotate_grid(&grid);
+            grid = grid.rotate_clockwise();
         }
         if let Some(prev_index) = seen.insert(grid.clone(), i) {
             if (1_000_000_000 - i) % (i - prev_index) == 0 {
@@ -64,3 +55,29 @@ fn part2(input: &[Vec<char>]) -> usize {
     }
     0
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "O....#....
+O.OO#....#
+.....##...
+OO.#O....O
+.O.....O#.
+O.#..O.#.#
+..O..#O..O
+.......O..
+#....###..
+#OO..#....";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(part1(&parse_input(EXAMPLE).unwrap()), 136);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(part2(&parse_input(EXAMPLE).unwrap()), 64);
+    }
+}
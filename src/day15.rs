@@ -1,12 +1,18 @@
 use crate::day15::Operation::{Add, Remove};
+use crate::util::error::*;
 use itertools::Itertools;
 
 #[aoc_generator(day15, part1)]
-fn parse_input(input: &str) -> Vec<Vec<u8>> {
+fn parse_input(input: &str) -> Result<Vec<Vec<u8>>, ParseError> {
     input
         .split(',')
-        .map(str::to_owned)
-        .map(String::into_bytes)
+        .map(|s| {
+            if s.is_empty() {
+                Err(ParseError::at(15, input, s, "empty step"))
+            } else {
+                Ok(s.as_bytes().to_vec())
+            }
+        })
         .collect()
 }
 
@@ -34,24 +40,24 @@ struct Instruction {
 }
 
 #[aoc_generator(day15, part2)]
-fn parse_instructions(input: &str) -> Vec<Instruction> {
+fn parse_instructions(input: &str) -> Result<Vec<Instruction>, ParseError> {
     input
         .split(',')
         .map(|s| {
             if let Some((label, length)) = s.split_once('=') {
-                Instruction {
+                Ok(Instruction {
+                    label: label.to_string(),
+                    hash: hash(label.as_bytes()),
+                    op: Add(parse_token(15, input, length)?),
+                })
+            } else if let Some(label) = s.strip_suffix('-') {
+                Ok(Instruction {
                     label: label.to_string(),
                     hash: hash(label.as_bytes()),
-                    op: Add(length.parse().unwrap()),
-                }
-            } else {
-                let label = s[0..s.len() - 1].to_string();
-                let hash = hash(label.as_bytes());
-                Instruction {
-                    label,
-                    hash,
                     op: Remove,
-                }
+                })
+            } else {
+                Err(ParseError::at(15, input, s, "expected `=` or `-`"))
             }
         })
         .collect()
@@ -93,3 +99,25 @@ fn part2(input: &[Instruction]) -> usize {
     }
     res
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7";
+
+    #[test]
+    fn hash_example() {
+        assert_eq!(hash(b"HASH"), 52);
+    }
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(part1(&parse_input(EXAMPLE).unwrap()), 1320);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(part2(&parse_instructions(EXAMPLE).unwrap()), 145);
+    }
+}
@@ -1,51 +1,41 @@
+use crate::util::error::*;
+use crate::util::grid::*;
+use crate::util::point::*;
 use rayon::prelude::*;
 #[derive(Clone, Debug)]
 struct Traversal {
-    layout: Vec<u8>,
-    rows: usize,
-    cols: usize,
+    layout: Grid<u8>,
+    // directions each tile has been entered in, one bit per direction
+    visited: Grid<u8>,
 }
 
-const UP: u8 = 1;
-const RIGHT: u8 = 2;
-const DOWN: u8 = 4;
-const LEFT: u8 = 8;
+const SPLITTER_H: u8 = b'-';
+const SPLITTER_V: u8 = b'|';
+const MIRROR_F: u8 = b'/';
+const MIRROR_B: u8 = b'\\';
 
-const SPLITTER_H: u8 = 0;
-const SPLITTER_V: u8 = 1;
-const MIRROR_F: u8 = 2;
-const MIRROR_B: u8 = 3;
-const OPEN: u8 = 4;
-
-impl Traversal {
-    fn idx(&self, row: usize, col: usize) -> usize {
-        row * self.cols + col
+fn dir_bit(dir: Point) -> u8 {
+    match dir {
+        UP => 1,
+        RIGHT => 2,
+        DOWN => 4,
+        LEFT => 8,
+        _ => unreachable!("Invalid direction"),
     }
+}
 
-    fn step(&self, row: usize, col: usize, dir: u8) -> Option<(usize, usize)> {
-        match dir {
-            UP if row == 0 => None,
-            UP => Some((row - 1, col)),
-            RIGHT if col == self.cols - 1 => None,
-            RIGHT => Some((row, col + 1)),
-            DOWN if row == self.rows - 1 => None,
-            DOWN => Some((row + 1, col)),
-            LEFT if col == 0 => None,
-            LEFT => Some((row, col - 1)),
-            _ => unreachable!("Invalid direction"),
-        }
+impl Traversal {
+    fn new(layout: Grid<u8>) -> Self {
+        let visited = Grid::new(layout.width, layout.height, 0);
+        Self { layout, visited }
     }
 
-    fn traverse(&mut self, mut row: usize, mut col: usize, mut dir: u8) -> usize {
+    fn traverse(&mut self, mut pos: Point, mut dir: Point) -> usize {
         let mut res = 0;
-        loop {
-            let idx = self.idx(row, col);
-            // LSB stores tile information
-            let tile = self.layout[idx] & 0xF;
-            // MSB stores visited information for memoization
-            let visited = self.layout[idx] >> 4;
+        while self.layout.contains(pos) {
+            let visited = self.visited[pos];
             // We have been here before from this direction
-            if dir & visited != 0 {
+            if dir_bit(dir) & visited != 0 {
                 break;
             }
             // We haven't touched this before
@@ -53,23 +43,15 @@ impl Traversal {
                 res += 1;
             }
             // Add direction to memoization
-            self.layout[idx] |= dir << 4;
-            match tile {
-                SPLITTER_H => {
-                    if dir == UP || dir == DOWN {
-                        if let Some((r, c)) = self.step(row, col, LEFT) {
-                            res += self.traverse(r, c, LEFT);
-                        }
-                        dir = RIGHT;
-                    }
+            self.visited[pos] |= dir_bit(dir);
+            match self.layout[pos] {
+                SPLITTER_H if dir == UP || dir == DOWN => {
+                    res += self.traverse(pos + LEFT, LEFT);
+                    dir = RIGHT;
                 }
-                SPLITTER_V => {
-                    if dir == LEFT || dir == RIGHT {
-                        if let Some((r, c)) = self.step(row, col, UP) {
-                            res += self.traverse(r, c, UP);
-                        }
-                        dir = DOWN;
-                    }
+                SPLITTER_V if dir == LEFT || dir == RIGHT => {
+                    res += self.traverse(pos + UP, UP);
+                    dir = DOWN;
                 }
                 MIRROR_F => {
                     dir = match dir {
@@ -91,55 +73,63 @@ impl Traversal {
                 }
                 _ => {}
             }
-            if let Some((r, c)) = self.step(row, col, dir) {
-                row = r;
-                col = c;
-            } else {
-                break;
-            }
+            pos += dir;
         }
         res
     }
 }
 #[aoc_generator(day16)]
-fn parse_input(input: &str) -> Traversal {
-    let cols = input.find('\n').unwrap();
-    let rows = input.len() / cols;
-    let layout = input
-        .chars()
-        .filter(|&c| c != '\n')
-        .map(|c| match c {
-            '.' => OPEN,
-            '\\' => MIRROR_B,
-            '/' => MIRROR_F,
-            '-' => SPLITTER_H,
-            '|' => SPLITTER_V,
-            _ => unreachable!("Invalid character"),
-        })
-        .collect();
-
-    Traversal { layout, rows, cols }
+fn parse_input(input: &str) -> Result<Traversal, ParseError> {
+    validate_grid(16, input, input, "./\\-|")?;
+    Ok(Traversal::new(Grid::parse(input)))
 }
 
 #[aoc(day16, part1)]
 fn part1(input: &Traversal) -> usize {
-    input.clone().traverse(0, 0, RIGHT)
+    input.clone().traverse(Point::ORIGIN, RIGHT)
 }
 
 #[aoc(day16, part2)]
 fn part2(input: &Traversal) -> usize {
+    let Grid { width, height, .. } = input.layout;
     let mut options = Vec::with_capacity(500);
-    for r in 0..input.rows {
-        options.push((r, 0, RIGHT));
-        options.push((r, input.cols - 1, LEFT));
+    for y in 0..height {
+        options.push((Point::new(0, y), RIGHT));
+        options.push((Point::new(width - 1, y), LEFT));
     }
-    for c in 0..input.cols {
-        options.push((0, c, DOWN));
-        options.push((input.rows - 1, c, UP));
+    for x in 0..width {
+        options.push((Point::new(x, 0), DOWN));
+        options.push((Point::new(x, height - 1), UP));
     }
     options
         .par_iter()
-        .map(|&(r, c, d)| input.clone().traverse(r, c, d))
+        .map(|&(pos, dir)| input.clone().traverse(pos, dir))
         .max()
         .unwrap()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = r".|...\....
+|.-.\.....
+.....|-...
+........|.
+..........
+.........\
+..../.\\..
+.-.-/..|..
+.|....-|.\
+..//.|....";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(part1(&parse_input(EXAMPLE).unwrap()), 46);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(part2(&parse_input(EXAMPLE).unwrap()), 51);
+    }
+}
@@ -1,61 +1,329 @@
-use std::collections::{BinaryHeap, HashMap};
-
-fn dijkstras(grid: &[Vec<u32>], minstep: isize, maxstep: isize) -> i64 {
-    let goal = (grid.len() - 1, grid[0].len() - 1);
-    let mut dists = HashMap::new();
-    let mut pq = BinaryHeap::new();
-    pq.push((0, (0, 0, (0, 0))));
-    while let Some((cost, (row, col, (dr, dc)))) = pq.pop() {
-        if (row, col) == goal {
-            return -cost;
+use crate::util::error::*;
+use crate::util::grid::*;
+use crate::util::point::*;
+use crate::util::search::*;
+
+/// Ways the crucible may change direction at the end of a straight run.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Turns {
+    pub left: bool,
+    pub right: bool,
+    pub reverse: bool,
+}
+
+impl Turns {
+    fn directions(self, dir: Point) -> impl Iterator<Item = Point> {
+        [
+            (self.left, dir.counter_clockwise()),
+            (self.right, dir.clockwise()),
+            (self.reverse, -dir),
+        ]
+        .into_iter()
+        .filter_map(|(allowed, next)| allowed.then_some(next))
+    }
+}
+
+/// How a crucible moves from `start` to `goal`, e.g. `CrucibleRules::crucible` for part 1.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct CrucibleRules {
+    /// Fewest blocks moved in a straight line before turning.
+    pub min_run: i32,
+    /// Most blocks moved in a straight line before having to turn.
+    pub max_run: i32,
+    /// Whether a run shorter than `min_run` may end at the goal.
+    pub stop_early: bool,
+    /// Turns allowed between runs, the first run may head any way.
+    pub turns: Turns,
+    pub start: Point,
+    pub goal: Point,
+}
+
+impl CrucibleRules {
+    /// The rules of part 1, from the top left to the bottom right of `grid`.
+    pub fn crucible(grid: &Grid<u8>) -> Self {
+        CrucibleRules {
+            min_run: 1,
+            max_run: 3,
+            stop_early: false,
+            turns: Turns {
+                left: true,
+                right: true,
+                reverse: false,
+            },
+            start: ORIGIN,
+            goal: Point::new(grid.width - 1, grid.height - 1),
         }
-        if dists
-            .get(&(row, col, (dr, dc)))
-            .is_some_and(|&old_cost| -cost > old_cost)
-        {
-            continue;
+    }
+
+    /// The rules of part 2, longer runs between the same corners as part 1.
+    pub fn ultra_crucible(grid: &Grid<u8>) -> Self {
+        CrucibleRules {
+            min_run: 4,
+            max_run: 10,
+            ..Self::crucible(grid)
         }
-        for next_step in [(-1, 0), (1, 0), (0, 1), (0, -1)] {
-            if next_step == (dr, dc) || next_step == (-dr, -dc) {
-                continue;
-            }
-            let mut new_cost = -cost;
-            for jump in 1..=maxstep {
-                let newr = (row as isize + next_step.0 * jump) as usize;
-                let newc = (col as isize + next_step.1 * jump) as usize;
-                if newr >= grid.len() || newc >= grid[0].len() {
-                    continue;
-                }
-                new_cost += i64::from(grid[newr][newc]);
-                if jump < minstep {
-                    continue;
-                }
-                if new_cost < *dists.get(&(newr, newc, next_step)).unwrap_or(&i64::MAX) {
-                    dists.insert((newr, newc, next_step), new_cost);
-                    pq.push((-new_cost, (newr, newc, next_step)));
+    }
+}
+
+struct Crucible<'a> {
+    grid: &'a Grid<u8>,
+    rules: &'a CrucibleRules,
+    // cheapest block, so the heuristic stays a lower bound even with zero-loss blocks
+    min_loss: u64,
+}
+
+impl Graph for Crucible<'_> {
+    // position and the direction of the straight run that ended there
+    type Node = (Point, Point);
+
+    fn successors(&self, &(pos, dir): &Self::Node) -> impl IntoIterator<Item = (Self::Node, u64)> {
+        let rules = self.rules;
+        let directions = if dir == ORIGIN {
+            ORTHOGONAL.to_vec()
+        } else {
+            rules.turns.directions(dir).collect()
+        };
+        let mut next = Vec::new();
+        // every move is a full straight run after a turn
+        for next_dir in directions {
+            let mut cost = 0;
+            for jump in 1..=rules.max_run {
+                let next_pos = pos + next_dir * jump;
+                let Some(&loss) = self.grid.get(next_pos) else {
+                    break;
+                };
+                cost += u64::from(loss);
+                if jump >= rules.min_run || (rules.stop_early && next_pos == rules.goal) {
+                    next.push(((next_pos, next_dir), cost));
                 }
             }
         }
+        next
+    }
+
+    fn heuristic(&self, &(pos, _): &Self::Node) -> u64 {
+        u64::from(pos.manhattan(self.rules.goal)) * self.min_loss
+    }
+
+    fn is_goal(&self, &(pos, _): &Self::Node) -> bool {
+        pos == self.rules.goal
+    }
+}
+
+/// One block entered by the crucible, and how many blocks in a row it has moved that way.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Step {
+    pub row: i32,
+    pub col: i32,
+    pub direction: Point,
+    pub run_length: i32,
+}
+
+/// The blocks a crucible enters from its start to its goal, and the heat lost on the way.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Route {
+    pub heat_loss: u64,
+    pub steps: Vec<Step>,
+}
+
+impl Route {
+    /// The grid of heat losses with the route drawn over it, as in the puzzle statement.
+    pub fn render(&self, grid: &Grid<u8>) -> String {
+        let mut canvas = grid.map(|&loss| b'0' + loss);
+        for step in &self.steps {
+            canvas[Point::new(step.col, step.row)] = match step.direction {
+                UP => b'^',
+                DOWN => b'v',
+                LEFT => b'<',
+                _ => b'>',
+            };
+        }
+        canvas.to_string()
     }
-    unreachable!("No path found")
+}
+
+fn best_route(grid: &Grid<u8>, rules: &CrucibleRules) -> Option<Route> {
+    if !grid.contains(rules.start) || !grid.contains(rules.goal) {
+        return None;
+    }
+    let min_loss = grid.bytes.iter().min().map_or(0, |&loss| u64::from(loss));
+    let crucible = Crucible {
+        grid,
+        rules,
+        min_loss,
+    };
+    let path = astar(&crucible, (rules.start, ORIGIN))?;
+    // each node ends a straight run, so fill in the blocks passed on the way
+    let steps = path
+        .nodes
+        .windows(2)
+        .flat_map(|pair| {
+            let [(from, _), (to, direction)] = [pair[0], pair[1]];
+            (1..=from.manhattan(to) as i32).map(move |run_length| {
+                let pos = from + direction * run_length;
+                Step {
+                    row: pos.y,
+                    col: pos.x,
+                    direction,
+                    run_length,
+                }
+            })
+        })
+        .collect();
+    Some(Route {
+        heat_loss: path.cost,
+        steps,
+    })
 }
 
 #[aoc_generator(day17)]
-fn parse_input(input: &str) -> Vec<Vec<u32>> {
-    input
-        .lines()
-        .map(|line| line.chars().filter_map(|c| c.to_digit(10)).collect())
-        .collect()
+fn parse_input(input: &str) -> Result<Grid<u8>, ParseError> {
+    validate_grid(17, input, input, "0123456789")?;
+    Ok(Grid::parse_with(input, |b| b - b'0'))
+}
+
+/// The best route through the input under the rules made for its grid, e.g.
+/// `route_with(input, CrucibleRules::crucible)`. None if the goal can't be reached.
+pub fn route_with(
+    input: &str,
+    rules: impl FnOnce(&Grid<u8>) -> CrucibleRules,
+) -> Result<Option<Route>, ParseError> {
+    let grid = parse_input(input)?;
+    Ok(best_route(&grid, &rules(&grid)))
+}
+
+/// The best route for a crucible, or an ultra crucible, drawn over the input.
+pub fn render_route(input: &str, ultra: bool) -> Result<Option<String>, ParseError> {
+    let grid = parse_input(input)?;
+    let rules = if ultra {
+        CrucibleRules::ultra_crucible(&grid)
+    } else {
+        CrucibleRules::crucible(&grid)
+    };
+    Ok(best_route(&grid, &rules).map(|route| route.render(&grid)))
 }
 
 #[aoc(day17, part1)]
-fn part1(input: &[Vec<u32>]) -> i64 {
-    let input = input.to_vec();
-    dijkstras(&input, 1, 3)
+fn part1(input: &Grid<u8>) -> Option<u64> {
+    best_route(input, &CrucibleRules::crucible(input)).map(|route| route.heat_loss)
 }
 
 #[aoc(day17, part2)]
-fn part2(input: &[Vec<u32>]) -> i64 {
-    let input = input.to_vec();
-    dijkstras(&input, 4, 10)
+fn part2(input: &Grid<u8>) -> Option<u64> {
+    best_route(input, &CrucibleRules::ultra_crucible(input)).map(|route| route.heat_loss)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "2413432311323
+3215453535623
+3255245654254
+3446585845452
+4546657867536
+1438598798454
+4457876987766
+3637877979653
+4654967986887
+4564679986453
+1224686865563
+2546548887735
+4322674655533";
+
+    const UNFORTUNATE: &str = "111111111111
+999999999991
+999999999991
+999999999991
+999999999991";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(part1(&parse_input(EXAMPLE).unwrap()), Some(102));
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(part2(&parse_input(EXAMPLE).unwrap()), Some(94));
+        assert_eq!(part2(&parse_input(UNFORTUNATE).unwrap()), Some(71));
+    }
+
+    #[test]
+    fn route_follows_the_rules() {
+        let grid = parse_input(EXAMPLE).unwrap();
+        let route = best_route(&grid, &CrucibleRules::ultra_crucible(&grid)).unwrap();
+        let losses = route
+            .steps
+            .iter()
+            .map(|s| u64::from(grid[Point::new(s.col, s.row)]));
+        assert_eq!(losses.sum::<u64>(), route.heat_loss);
+        let last = route.steps.last().unwrap();
+        assert_eq!((last.row, last.col), (12, 12));
+        assert!(route.steps.iter().all(|s| (1..=10).contains(&s.run_length)));
+    }
+
+    #[test]
+    fn render_example() {
+        let expected = "2>>34^>>>1323
+32v>>>35v5623
+32552456v>>54
+3446585845v52
+4546657867v>6
+14385987984v4
+44578769877v6
+36378779796v>
+465496798688v
+456467998645v
+12246868655<v
+25465488877v5
+43226746555v>
+";
+        assert_eq!(render_route(EXAMPLE, false).unwrap().unwrap(), expected);
+    }
+
+    #[test]
+    fn rule_variants() {
+        let grid = parse_input(UNFORTUNATE).unwrap();
+        let ultra = CrucibleRules::ultra_crucible(&grid);
+        // 11 blocks right then 4 down, stopping after a short run is not allowed
+        assert_eq!(best_route(&grid, &ultra).unwrap().heat_loss, 71);
+        let stop_early = CrucibleRules {
+            stop_early: true,
+            ..ultra
+        };
+        assert!(best_route(&grid, &stop_early).unwrap().heat_loss < 71);
+
+        // heading left from the second block is too short a run, so the only way is to bounce back
+        let grid = parse_input("1111").unwrap();
+        let rules = CrucibleRules {
+            min_run: 2,
+            start: Point::new(1, 0),
+            goal: ORIGIN,
+            ..CrucibleRules::crucible(&grid)
+        };
+        assert_eq!(best_route(&grid, &rules), None);
+        let reverse = CrucibleRules {
+            turns: Turns {
+                reverse: true,
+                ..rules.turns
+            },
+            ..rules
+        };
+        assert_eq!(best_route(&grid, &reverse).unwrap().heat_loss, 5);
+        let outside = CrucibleRules {
+            goal: Point::new(4, 0),
+            ..reverse
+        };
+        assert_eq!(best_route(&grid, &outside), None);
+    }
+
+    #[test]
+    fn zero_loss_blocks() {
+        // the detour through the zeros costs 3, straight along the top costs 4
+        let route = route_with("1112\n0009", |grid| CrucibleRules {
+            goal: Point::new(3, 0),
+            ..CrucibleRules::crucible(grid)
+        });
+        assert_eq!(route.unwrap().unwrap().heat_loss, 3);
+    }
 }
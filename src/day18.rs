@@ -1,109 +1,100 @@
+use crate::util::error::*;
+use crate::util::geometry;
+use crate::util::point::Point;
 use itertools::Itertools;
-use std::ops::{Add, Mul};
-#[derive(Debug, Copy, Clone)]
-struct Point {
-    x: i64,
-    y: i64,
-}
-
-impl Point {
-    fn origin() -> Self {
-        Self::new(0, 0)
-    }
-    fn new(x: i64, y: i64) -> Self {
-        Self { x, y }
-    }
-    fn from_direction(dir: char) -> Self {
-        match dir {
-            '0' | 'R' => Self::new(1, 0),
-            '1' | 'D' => Self::new(0, -1),
-            '2' | 'L' => Self::new(-1, 0),
-            '3' | 'U' => Self::new(0, 1),
-            _ => unreachable!("Malformed input"),
-        }
-    }
-}
-
-impl Add for Point {
-    type Output = Self;
 
-    fn add(self, rhs: Self) -> Self::Output {
-        Self::Output {
-            x: self.x + rhs.x,
-            y: self.y + rhs.y,
-        }
+fn step(dir: char) -> Point<i64> {
+    match dir {
+        '0' | 'R' => Point::RIGHT,
+        '1' | 'D' => Point::DOWN,
+        '2' | 'L' => Point::LEFT,
+        '3' | 'U' => Point::UP,
+        _ => unreachable!("Malformed input"),
     }
 }
-impl Mul<i64> for Point {
-    type Output = Self;
 
-    fn mul(self, rhs: i64) -> Self::Output {
-        Self::Output {
-            x: self.x * rhs,
-            y: self.y * rhs,
-        }
-    }
-}
 #[aoc_generator(day18)]
-fn parse_input(input: &str) -> Vec<(char, i64, String)> {
+fn parse_input(input: &str) -> Result<Vec<(char, i64, String)>, ParseError> {
     input
         .lines()
         .map(|line| {
-            let (dir, size, color) = line
-                .split_ascii_whitespace()
-                .collect_tuple()
-                .expect("malformed input");
+            let (dir, size, color) =
+                line.split_ascii_whitespace()
+                    .collect_tuple()
+                    .ok_or_else(|| {
+                        ParseError::at(18, input, line, "expected `<dir> <size> (#<color>)`")
+                    })?;
 
-            let dir = dir.chars().next().unwrap();
-            let size = size.parse::<i64>().unwrap();
-            let color = color.strip_prefix("(#").unwrap().strip_suffix(')').unwrap();
+            let dir = match dir {
+                "U" | "D" | "L" | "R" => dir.chars().next().unwrap(),
+                _ => return Err(ParseError::at(18, input, dir, "unknown direction")),
+            };
+            let size = parse_token(18, input, size)?;
+            let color = color
+                .strip_prefix("(#")
+                .and_then(|c| c.strip_suffix(')'))
+                .filter(|c| c.len() == 6 && c.bytes().all(|b| b.is_ascii_hexdigit()))
+                .filter(|c| matches!(c.as_bytes()[5], b'0'..=b'3'))
+                .ok_or_else(|| ParseError::at(18, input, color, "expected `(#xxxxxd)` color"))?;
 
-            (dir, size, color.to_owned())
+            Ok((dir, size, color.to_owned()))
         })
         .collect()
 }
 
-fn get_capacity(dig_plan: Vec<Point>) -> i64 {
+fn get_capacity(dig_plan: impl IntoIterator<Item = (char, i64)>) -> i64 {
     let corners: Vec<_> = dig_plan
-        .iter()
-        .scan(Point::origin(), |state, &point| {
-            let res = Some(state.add(point));
-            *state = state.add(point);
-            res
+        .into_iter()
+        .scan(Point::ORIGIN, |corner, (dir, size)| {
+            *corner += step(dir) * size;
+            Some(*corner)
         })
         .collect();
-
-    let perimeter = dig_plan
-        .into_iter()
-        .fold(0, |acc, point| acc + point.x.abs() + point.y.abs());
-
-    let area = corners.into_iter().tuple_windows().fold(
-        0,
-        |acc, (Point { x: x0, y: y0 }, Point { x: x1, y: y1 })| acc + (x0 * y1 - x1 * y0),
-    );
-
-    let area = (area / 2).abs();
-    area + (perimeter / 2) + 1
+    geometry::boundary_plus_interior(&corners)
 }
+
 #[aoc(day18, part1)]
 fn part1(input: &[(char, i64, String)]) -> i64 {
-    let dig_plan: Vec<_> = input
-        .iter()
-        .map(|&(dir, size, _)| Point::from_direction(dir) * size)
-        .collect();
-    get_capacity(dig_plan)
+    get_capacity(input.iter().map(|&(dir, size, _)| (dir, size)))
 }
 
 #[aoc(day18, part2)]
 fn part2(input: &[(char, i64, String)]) -> i64 {
-    let dig_plan: Vec<_> = input
-        .iter()
-        .map(|(_, _, color)| {
-            let color = color.clone();
-            let (size, dir) = color.split_at(color.len() - 1);
-            Point::from_direction(dir.chars().next().unwrap())
-                * i64::from_str_radix(size, 16).unwrap()
-        })
-        .collect();
-    get_capacity(dig_plan)
+    get_capacity(input.iter().map(|(_, _, color)| {
+        let (size, dir) = color.split_at(color.len() - 1);
+        (
+            dir.chars().next().unwrap(),
+            i64::from_str_radix(size, 16).unwrap(),
+        )
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "R 6 (#70c710)
+D 5 (#0dc571)
+L 2 (#5713f0)
+D 2 (#d2c081)
+R 2 (#59c680)
+D 2 (#411b91)
+L 5 (#8ceee2)
+U 2 (#caa173)
+L 1 (#1b58a2)
+U 2 (#caa171)
+R 2 (#7807d2)
+U 3 (#a77fa3)
+L 2 (#015232)
+U 2 (#7a21e3)";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(part1(&parse_input(EXAMPLE).unwrap()), 62);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(part2(&parse_input(EXAMPLE).unwrap()), 952_408_144_115);
+    }
 }
@@ -1,39 +1,112 @@
-use lazy_static::lazy_static;
-use regex::{Match, Regex};
-use std::cmp::Ordering;
-use std::collections::{HashMap, VecDeque};
-use std::str::FromStr;
-
-lazy_static! {
-    static ref PART_REGEX: Regex = Regex::new(r"^\{x=(\d+),m=(\d+),a=(\d+),s=(\d+)\}$").unwrap();
+use crate::util::error::*;
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+
+/// Bounds on every rating in the puzzle.
+const RATINGS: Range = Range(1, 4000);
+
+/// Rating category names, indexed by id in the order they first appear in the input.
+#[derive(Debug, Default, Clone, PartialEq)]
+struct Categories(Vec<String>);
+
+impl Categories {
+    fn id(&self, name: &str) -> Option<usize> {
+        self.0.iter().position(|n| n == name)
+    }
+
+    fn intern(&mut self, name: &str) -> usize {
+        self.id(name).unwrap_or_else(|| {
+            self.0.push(name.to_string());
+            self.0.len() - 1
+        })
+    }
+
+    // `token` is a slice of `line` so errors can point into it
+    fn parse(&mut self, line: &str, token: &str) -> Result<usize, ParseError> {
+        if token.is_empty() || !token.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(ParseError::at(
+                19,
+                line,
+                token,
+                "expected a rating category",
+            ));
+        }
+        Ok(self.intern(token))
+    }
 }
-#[derive(Debug)]
-struct ParseError;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Comparison {
+    Less,
+    LessEq,
+    Greater,
+    GreaterEq,
+    Equal,
+    NotEqual,
+}
+
+impl Comparison {
+    // longest first so `<=` isn't read as `<`
+    const SYMBOLS: [(&'static str, Comparison); 6] = [
+        ("<=", Comparison::LessEq),
+        (">=", Comparison::GreaterEq),
+        ("!=", Comparison::NotEqual),
+        ("<", Comparison::Less),
+        (">", Comparison::Greater),
+        ("=", Comparison::Equal),
+    ];
+
+    fn negate(self) -> Self {
+        match self {
+            Comparison::Less => Comparison::GreaterEq,
+            Comparison::LessEq => Comparison::Greater,
+            Comparison::Greater => Comparison::LessEq,
+            Comparison::GreaterEq => Comparison::Less,
+            Comparison::Equal => Comparison::NotEqual,
+            Comparison::NotEqual => Comparison::Equal,
+        }
+    }
+
+    /// Every rating passing the comparison against `rhs`.
+    fn passing(self, rhs: u32) -> Vec<Range> {
+        let below = rhs.checked_sub(1).map(|end| Range(0, end));
+        let above = rhs.checked_add(1).map(|start| Range(start, u32::MAX));
+        match self {
+            Comparison::Less => below.into_iter().collect(),
+            Comparison::LessEq => vec![Range(0, rhs)],
+            Comparison::Greater => above.into_iter().collect(),
+            Comparison::GreaterEq => vec![Range(rhs, u32::MAX)],
+            Comparison::Equal => vec![Range(rhs, rhs)],
+            Comparison::NotEqual => below.into_iter().chain(above).collect(),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
 struct WorkflowStep {
-    property: char,
-    comp: Ordering,
+    property: usize,
+    comp: Comparison,
     rhs: u32,
     dest: String,
 }
 
-impl FromStr for WorkflowStep {
-    type Err = ParseError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (test, dest) = s.split_once(':').ok_or(ParseError)?;
-        let (beginning, rhs) = test.split_at(2);
-        let rhs = rhs.parse::<u32>().map_err(|_| ParseError)?;
-        let (prop, comp) = {
-            let mut c = beginning.chars();
-            let prop = c.next().ok_or(ParseError)?;
-            let comp = match c.next().ok_or(ParseError)? {
-                '<' => Ordering::Less,
-                '>' => Ordering::Greater,
-                _ => return Err(ParseError),
-            };
-            (prop, comp)
-        };
+impl WorkflowStep {
+    // `step` is a slice of the workflow `line` so errors can point into it
+    fn parse(line: &str, step: &str, categories: &mut Categories) -> Result<Self, ParseError> {
+        let err = |token, message| ParseError::at(19, line, token, message);
+        let (test, dest) = step
+            .split_once(':')
+            .ok_or_else(|| err(step, "expected `<test>:<destination>`"))?;
+        let split = test
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(test.len());
+        let (prop, test) = test.split_at(split);
+        let prop = categories.parse(line, prop)?;
+        let (comp, rhs) = Comparison::SYMBOLS
+            .iter()
+            .find_map(|&(symbol, comp)| Some((comp, test.strip_prefix(symbol)?)))
+            .ok_or_else(|| err(test, "expected one of `<`, `<=`, `>`, `>=`, `=` or `!=`"))?;
+        let rhs = parse_token(19, line, rhs)?;
         Ok(Self {
             property: prop,
             comp,
@@ -43,49 +116,37 @@ impl FromStr for WorkflowStep {
     }
 }
 
+/// Ratings indexed by category id.
 #[derive(Debug, PartialEq)]
-struct Part {
-    x: u32,
-    m: u32,
-    a: u32,
-    s: u32,
-}
+struct Part(Vec<u32>);
 
 impl Part {
-    fn get(&self, property: char) -> u32 {
-        match property {
-            'x' => self.x,
-            'm' => self.m,
-            'a' => self.a,
-            's' => self.s,
-            _ => unreachable!("Malformed property"),
-        }
-    }
-
-    fn matches(&self, step: &WorkflowStep) -> bool {
-        self.get(step.property).cmp(&step.rhs) == step.comp
-    }
-
-    fn total(&self) -> u32 {
-        self.x + self.m + self.a + self.s
+    fn total(&self) -> u64 {
+        self.0.iter().map(|&rating| u64::from(rating)).sum()
     }
 }
 
-impl FromStr for Part {
-    type Err = ParseError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let captures = PART_REGEX.captures(s).ok_or(ParseError)?;
-        let process_cap = |cap: Match| cap.as_str().parse::<u32>().map_err(|_| ParseError);
-
-        Ok(Self {
-            x: process_cap(captures.get(1).ok_or(ParseError)?)?,
-            m: process_cap(captures.get(2).ok_or(ParseError)?)?,
-            a: process_cap(captures.get(3).ok_or(ParseError)?)?,
-            s: process_cap(captures.get(4).ok_or(ParseError)?)?,
-        })
+// the ratings a part lists, as (category id, rating) pairs
+fn parse_ratings(line: &str, categories: &mut Categories) -> Result<Vec<(usize, u32)>, ParseError> {
+    let err = |token, message| ParseError::at(19, line, token, message);
+    let inner = line
+        .strip_prefix('{')
+        .and_then(|line| line.strip_suffix('}'))
+        .ok_or_else(|| err(line, "expected `{<category>=<rating>,..}`"))?;
+    let mut ratings: Vec<(usize, u32)> = Vec::new();
+    for rating in inner.split(',') {
+        let (name, value) = rating
+            .split_once('=')
+            .ok_or_else(|| err(rating, "expected `<category>=<rating>`"))?;
+        let id = categories.parse(line, name)?;
+        if ratings.iter().any(|&(other, _)| other == id) {
+            return Err(err(name, "duplicate rating"));
+        }
+        ratings.push((id, parse_token(19, line, value)?));
     }
+    Ok(ratings)
 }
+
 #[derive(Debug, PartialEq)]
 struct Workflow {
     name: String,
@@ -94,211 +155,487 @@ struct Workflow {
 }
 
 impl Workflow {
-    fn process(&self, part: &Part) -> &str {
-        if let Some(matching) = self.steps.iter().find(|&step| part.matches(step)) {
-            &matching.dest
-        } else {
-            &self.default
-        }
-    }
-
-    fn process_state(&self, state: PartState, queue: &mut VecDeque<(PartState, String)>) {
-        let mut curr_state = Some(state);
-
-        for step in &self.steps {
-            if curr_state.is_none() {
-                return;
-            }
-
-            let state = curr_state.unwrap();
-            let (stay, go) = state.split(step);
-
-            if let Some(go) = go {
-                queue.push_back(go);
-            }
-
-            curr_state = stay;
-        }
-
-        if let Some(default) = curr_state {
-            queue.push_back((default, self.default.to_string()));
-        }
-    }
-}
-
-impl FromStr for Workflow {
-    type Err = ParseError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let s = s.strip_suffix('}').ok_or(ParseError)?;
-        let (name, steps) = s.split_once('{').ok_or(ParseError)?;
-        let last_comma = steps
-            .chars()
-            .rev()
-            .position(|c| c == ',')
-            .ok_or(ParseError)?;
-        let last_comma = steps.len() - last_comma - 1;
-        let (steps, default) = steps.split_at(last_comma);
-        let default = &default[1..];
+    fn parse(s: &str, categories: &mut Categories) -> Result<Self, ParseError> {
+        let (name, steps) = s
+            .strip_suffix('}')
+            .and_then(|s| s.split_once('{'))
+            .ok_or_else(|| ParseError::at(19, s, s, "expected `<name>{<steps>}`"))?;
+        let (steps, default) = steps
+            .rsplit_once(',')
+            .ok_or_else(|| ParseError::at(19, s, steps, "expected a default destination"))?;
         Ok(Self {
             name: name.to_string(),
-            steps: steps.split(',').filter_map(|s| s.parse().ok()).collect(),
+            steps: steps
+                .split(',')
+                .map(|step| WorkflowStep::parse(s, step, categories))
+                .collect::<Result<_, _>>()?,
             default: default.to_string(),
         })
     }
 }
 #[derive(Debug)]
 struct Input {
-    workflows: HashMap<String, Workflow>,
+    categories: Categories,
+    /// Every combination of ratings the workflows accept, as disjoint boxes.
+    accepted: Vec<PartState>,
     parts: Vec<Part>,
 }
 
 impl Input {
-    fn process(&self, part: &Part) -> String {
-        let mut current = "in";
-        while let Some(next) = self.workflows.get(current) {
-            current = next.process(part);
+    fn accepts(&self, part: &Part) -> bool {
+        self.accepted.iter().any(|state| state.contains(part))
+    }
+
+    /// How many accepted combinations lie within `bounds`, which has a range for every
+    /// category indexed by id. None if it doesn't.
+    fn count_accepted(&self, bounds: &[Range]) -> Option<u64> {
+        if bounds.len() != self.categories.0.len() {
+            return None;
+        }
+        let bounds = PartState(bounds.to_vec());
+        let count = self
+            .accepted
+            .iter()
+            .filter_map(|state| state.intersect(&bounds))
+            .map(|state| state.distinct_combinations())
+            .sum();
+        Some(count)
+    }
+}
+
+// Sends `state` through the named workflow, collecting whatever ends up accepted.
+// `workflows` keeps the line each one was read from, and `from` is the line sending `state`
+// here, so errors can point at it. `chain` holds the workflows already on the way here, as
+// revisiting one would never end.
+fn compile<'a>(
+    input: &str,
+    workflows: &'a HashMap<String, (Workflow, &str)>,
+    from: &str,
+    name: &'a str,
+    state: PartState,
+    chain: &mut Vec<&'a str>,
+    accepted: &mut Vec<PartState>,
+) -> Result<(), ParseError> {
+    match name {
+        "A" => accepted.push(state),
+        "R" => {}
+        _ => {
+            let err = |message| ParseError::at(19, input, from, message);
+            let (workflow, line) = workflows
+                .get(name)
+                .ok_or_else(|| err(format!("unknown workflow `{name}`")))?;
+            if chain.contains(&name) {
+                return Err(err(format!("workflow `{name}` loops")));
+            }
+            chain.push(name);
+            let mut remaining = vec![state];
+            for step in &workflow.steps {
+                let mut stays = Vec::new();
+                for current in remaining {
+                    let (stay, go) = current.split(step);
+                    for go in go {
+                        compile(input, workflows, line, &step.dest, go, chain, accepted)?;
+                    }
+                    stays.extend(stay);
+                }
+                remaining = stays;
+            }
+            for rest in remaining {
+                compile(
+                    input,
+                    workflows,
+                    line,
+                    &workflow.default,
+                    rest,
+                    chain,
+                    accepted,
+                )?;
+            }
+            chain.pop();
         }
-        current.to_string()
     }
+    Ok(())
 }
 
 #[aoc_generator(day19)]
-fn parse_input(input: &str) -> Input {
-    let (workflows, parts) = input.split_once("\n\n").expect("Malformed input");
-    let workflows = workflows
-        .lines()
-        .map(|line| {
-            let workflow = line.parse::<Workflow>().unwrap();
-            (workflow.name.clone(), workflow)
+fn parse_input(input: &str) -> Result<Input, ParseError> {
+    let mut categories = Categories::default();
+    let mut workflows = HashMap::new();
+    let mut ratings = Vec::new();
+    let mut lines = input.lines().enumerate();
+    for (i, line) in lines.by_ref().take_while(|(_, line)| !line.is_empty()) {
+        let workflow = Workflow::parse(line, &mut categories).map_err(|e| e.offset_lines(i))?;
+        if workflows.contains_key(&workflow.name) {
+            let message = format!("duplicate workflow `{}`", workflow.name);
+            return Err(ParseError::new(19, i + 1, 1, message));
+        }
+        workflows.insert(workflow.name.clone(), (workflow, line));
+    }
+    for (i, line) in lines {
+        let rating = parse_ratings(line, &mut categories).map_err(|e| e.offset_lines(i))?;
+        ratings.push((i, rating));
+    }
+
+    // categories can turn up on any line, so parts are only complete once everything is read
+    let parts = ratings
+        .into_iter()
+        .map(|(i, rating)| {
+            let mut part = vec![None; categories.0.len()];
+            for (id, value) in rating {
+                part[id] = Some(value);
+            }
+            part.into_iter()
+                .enumerate()
+                .map(|(id, value)| {
+                    let message = format!("missing rating `{}`", categories.0[id]);
+                    value.ok_or_else(|| ParseError::new(19, i + 1, 1, message))
+                })
+                .collect::<Result<_, _>>()
+                .map(Part)
         })
-        .collect::<HashMap<_, _>>();
-    let parts = parts.lines().filter_map(|line| line.parse().ok()).collect();
+        .collect::<Result<_, _>>()?;
 
-    Input { workflows, parts }
+    // compiled over every possible rating so that any bounds can be queried afterwards
+    let everything = PartState(vec![Range(0, u32::MAX); categories.0.len()]);
+    let mut accepted = Vec::new();
+    compile(
+        input,
+        &workflows,
+        input,
+        "in",
+        everything,
+        &mut Vec::new(),
+        &mut accepted,
+    )?;
+    Ok(Input {
+        categories,
+        accepted,
+        parts,
+    })
 }
 
 #[aoc(day19, part1)]
-fn part1(input: &Input) -> u32 {
+fn part1(input: &Input) -> u64 {
     input
         .parts
         .iter()
-        .filter_map(|part| {
-            if input.process(part) == "A" {
-                Some(part.total())
-            } else {
-                None
-            }
-        })
+        .filter(|part| input.accepts(part))
+        .map(Part::total)
         .sum()
 }
 #[derive(Debug, Copy, Clone, PartialEq)]
 struct Range(u32, u32);
 
 impl Range {
-    fn range(self) -> u32 {
-        self.1 - self.0 + 1
+    fn range(self) -> u64 {
+        u64::from(self.1 - self.0) + 1
+    }
+
+    fn contains(self, value: u32) -> bool {
+        (self.0..=self.1).contains(&value)
+    }
+
+    fn intersect(self, other: Self) -> Option<Self> {
+        let (start, end) = (self.0.max(other.0), self.1.min(other.1));
+        (start <= end).then_some(Self(start, end))
     }
     // returns (stay, go)
-    fn split(self, comp: Ordering, rhs: u32) -> (Option<Self>, Option<Self>) {
-        match comp {
-            Ordering::Less => {
-                if rhs <= self.0 {
-                    (Some(self), None)
-                } else if rhs > self.1 {
-                    (None, Some(self))
-                } else {
-                    (
-                        Some(Self(rhs, self.1)),
-                        Some(Self(self.0, rhs.saturating_sub(1))),
-                    )
-                }
-            }
-            Ordering::Equal => (None, None),
-            Ordering::Greater => {
-                if rhs >= self.1 {
-                    (Some(self), None)
-                } else if rhs < self.0 {
-                    (None, Some(self))
-                } else {
-                    (Some(Self(self.0, rhs)), Some(Self(rhs + 1, self.1)))
-                }
-            }
-        }
+    fn split(self, comp: Comparison, rhs: u32) -> (Vec<Self>, Vec<Self>) {
+        let within = |comp: Comparison| {
+            comp.passing(rhs)
+                .into_iter()
+                .filter_map(|range| self.intersect(range))
+                .collect()
+        };
+        (within(comp.negate()), within(comp))
     }
 }
-#[derive(Debug, PartialEq)]
-struct PartState {
-    x: Range,
-    m: Range,
-    a: Range,
-    s: Range,
-}
+/// A range of ratings for every category, indexed by category id.
+#[derive(Debug, Clone, PartialEq)]
+struct PartState(Vec<Range>);
 
 impl PartState {
-    fn initial() -> Self {
-        Self {
-            x: Range(1, 4000),
-            m: Range(1, 4000),
-            a: Range(1, 4000),
-            s: Range(1, 4000),
-        }
+    fn get(&self, property: usize) -> Range {
+        self.0[property]
     }
 
-    fn get(&self, property: char) -> Range {
-        match property {
-            'x' => self.x,
-            'm' => self.m,
-            'a' => self.a,
-            's' => self.s,
-            _ => unreachable!("Malformed property"),
-        }
+    fn replace_range(&self, property: usize, new: Range) -> Self {
+        let mut state = self.clone();
+        state.0[property] = new;
+        state
+    }
+    // returns (stay, go)
+    fn split(&self, step: &WorkflowStep) -> (Vec<PartState>, Vec<PartState>) {
+        let (stay, go) = self.get(step.property).split(step.comp, step.rhs);
+        let replace = |ranges: Vec<Range>| {
+            ranges
+                .into_iter()
+                .map(|range| self.replace_range(step.property, range))
+                .collect()
+        };
+        (replace(stay), replace(go))
     }
 
-    fn replace_range(&self, property: char, new: Range) -> Self {
-        match property {
-            'x' => Self { x: new, ..*self },
-            'm' => Self { m: new, ..*self },
-            'a' => Self { a: new, ..*self },
-            's' => Self { s: new, ..*self },
-            _ => unreachable!("Malformed property"),
-        }
+    fn contains(&self, part: &Part) -> bool {
+        self.0
+            .iter()
+            .zip(&part.0)
+            .all(|(range, &value)| range.contains(value))
     }
-    fn split(&self, step: &WorkflowStep) -> (Option<PartState>, Option<(PartState, String)>) {
-        let (stay, go) = self.get(step.property).split(step.comp, step.rhs);
-        let stay = stay.map(|range| self.replace_range(step.property, range));
 
-        let next_state = go.map(|range| {
-            (
-                self.replace_range(step.property, range),
-                step.dest.to_string(),
-            )
-        });
-        (stay, next_state)
+    fn intersect(&self, other: &Self) -> Option<Self> {
+        self.0
+            .iter()
+            .zip(&other.0)
+            .map(|(a, &b)| a.intersect(b))
+            .collect::<Option<_>>()
+            .map(PartState)
     }
 
     fn distinct_combinations(&self) -> u64 {
-        u64::from(self.x.range())
-            * u64::from(self.m.range())
-            * u64::from(self.a.range())
-            * u64::from(self.s.range())
+        self.0.iter().map(|range| range.range()).product()
     }
 }
 
-#[aoc(day19, part2)]
-fn part2(input: &Input) -> u64 {
-    let mut res = 0;
-    let mut q = VecDeque::new();
-    q.push_back((PartState::initial(), "in".to_string()));
-    while let Some((state, workflow)) = q.pop_front() {
-        if workflow == "A" {
-            res += state.distinct_combinations();
-            continue;
+/// How many combinations of ratings the input's workflows accept within `bounds`, an
+/// inclusive range for each category by name. None unless every category appears once.
+pub fn count_accepted(
+    input: &str,
+    bounds: &[(&str, RangeInclusive<u32>)],
+) -> Result<Option<u64>, ParseError> {
+    let input = parse_input(input)?;
+    let mut ranges = vec![None; input.categories.0.len()];
+    for (name, bound) in bounds {
+        let Some(id) = input.categories.id(name) else {
+            return Ok(None);
+        };
+        if ranges[id]
+            .replace(Range(*bound.start(), *bound.end()))
+            .is_some()
+        {
+            return Ok(None);
         }
+    }
+    let ranges = ranges.into_iter().collect::<Option<Vec<_>>>();
+    Ok(ranges.and_then(|ranges| input.count_accepted(&ranges)))
+}
 
-        if let Some(next) = input.workflows.get(&workflow) {
-            next.process_state(state, &mut q);
-        }
+#[aoc(day19, part2)]
+fn part2(input: &Input) -> Option<u64> {
+    input.count_accepted(&vec![RATINGS; input.categories.0.len()])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "px{a<2006:qkq,m>2090:A,rfg}
+pv{a>1716:R,A}
+lnx{m>1548:A,A}
+rfg{s<537:gd,x>2440:R,A}
+qs{s>3448:A,lnx}
+qkq{x<1416:A,crn}
+crn{x>2662:A,R}
+in{s<1351:px,qqz}
+qqz{s>2770:qs,m<1801:hdj,R}
+gd{a>3333:R,R}
+hdj{m>838:A,pv}
+
+{x=787,m=2655,a=1222,s=2876}
+{x=1679,m=44,a=2067,s=496}
+{x=2036,m=264,a=79,s=2244}
+{x=2461,m=1339,a=466,s=291}
+{x=2127,m=1623,a=2188,s=1013}";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(part1(&parse_input(EXAMPLE).unwrap()), 19114);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(
+            part2(&parse_input(EXAMPLE).unwrap()),
+            Some(167_409_079_868_000)
+        );
+    }
+
+    #[test]
+    fn split_less() {
+        let range = Range(1, 4000);
+        assert_eq!(
+            range.split(Comparison::Less, 2006),
+            (vec![Range(2006, 4000)], vec![Range(1, 2005)])
+        );
+        assert_eq!(range.split(Comparison::Less, 1), (vec![range], vec![]));
+        assert_eq!(range.split(Comparison::Less, 4001), (vec![], vec![range]));
+        assert_eq!(
+            range.split(Comparison::LessEq, 1),
+            (vec![Range(2, 4000)], vec![Range(1, 1)])
+        );
+    }
+
+    #[test]
+    fn split_greater() {
+        let range = Range(1, 4000);
+        assert_eq!(
+            range.split(Comparison::Greater, 2090),
+            (vec![Range(1, 2090)], vec![Range(2091, 4000)])
+        );
+        assert_eq!(
+            range.split(Comparison::Greater, 4000),
+            (vec![range], vec![])
+        );
+        assert_eq!(range.split(Comparison::Greater, 0), (vec![], vec![range]));
+        assert_eq!(
+            range.split(Comparison::GreaterEq, 4000),
+            (vec![Range(1, 3999)], vec![Range(4000, 4000)])
+        );
+    }
+
+    #[test]
+    fn split_equality() {
+        let range = Range(1, 4000);
+        assert_eq!(
+            range.split(Comparison::Equal, 10),
+            (vec![Range(1, 9), Range(11, 4000)], vec![Range(10, 10)])
+        );
+        assert_eq!(
+            range.split(Comparison::NotEqual, 10),
+            (vec![Range(10, 10)], vec![Range(1, 9), Range(11, 4000)])
+        );
+        assert_eq!(range.split(Comparison::NotEqual, 0), (vec![], vec![range]));
+        let max = Range(u32::MAX, u32::MAX);
+        assert_eq!(
+            max.split(Comparison::Greater, u32::MAX),
+            (vec![max], vec![])
+        );
+    }
+
+    #[test]
+    fn inclusive_and_equality_rules() {
+        let input = parse_input("in{x<=10:A,m=5:A,a!=7:R,A}\n\n{x=11,m=5,a=7,s=1}").unwrap();
+        assert_eq!(part1(&input), 24);
+        let mut bounds = vec![Range(1, 10); 4];
+        // x <= 10 covers everything, so nothing reaches the other rules
+        assert_eq!(input.count_accepted(&bounds), Some(10_000));
+        bounds[input.categories.id("x").unwrap()] = Range(11, 20);
+        // a tenth pass on m = 5, then a tenth of the rest pass on a = 7 by default
+        assert_eq!(input.count_accepted(&bounds), Some(1000 + 900));
+    }
+
+    #[test]
+    fn public_query() {
+        let input = "in{x<=10:A,m=5:A,a!=7:R,A}\n\n{x=11,m=5,a=7,s=1}";
+        let bounds = [("s", 1..=10), ("a", 1..=10), ("m", 1..=10), ("x", 11..=20)];
+        assert_eq!(count_accepted(input, &bounds), Ok(Some(1000 + 900)));
+        assert_eq!(count_accepted(input, &bounds[1..]), Ok(None));
+        let repeated = [("s", 1..=10), ("a", 1..=10), ("m", 1..=10), ("m", 1..=10)];
+        assert_eq!(count_accepted(input, &repeated), Ok(None));
+        let unknown = [("s", 1..=10), ("a", 1..=10), ("m", 1..=10), ("q", 1..=10)];
+        assert_eq!(count_accepted(input, &unknown), Ok(None));
+    }
+
+    #[test]
+    fn large_ratings_total() {
+        let input = parse_input("in{x>1:A,R}\n\n{x=4294967295,m=4294967295}").unwrap();
+        assert_eq!(part1(&input), 2 * u64::from(u32::MAX));
+    }
+
+    #[test]
+    fn reports_bad_rule() {
+        let input = "in{x<10:A,m?5:R,A}\n\n{x=1,m=2,a=3,s=4}";
+        assert_eq!(
+            parse_input(input).unwrap_err(),
+            ParseError::new(
+                19,
+                1,
+                12,
+                "expected one of `<`, `<=`, `>`, `>=`, `=` or `!=`"
+            )
+        );
+    }
+
+    #[test]
+    fn reports_bad_part() {
+        let input = "in{x<10:A,R}\n\n{x=1,m=2,a=3,s=4}\n{x=1,m=2,a=3}";
+        assert_eq!(parse_input(input).unwrap_err().line, 4);
+    }
+
+    #[test]
+    fn accepted_within_bounds() {
+        let input = parse_input(EXAMPLE).unwrap();
+        let all = [RATINGS; 4];
+        let x = input.categories.id("x").unwrap();
+        let (mut low_x, mut high_x) = (all, all);
+        low_x[x] = Range(1, 100);
+        high_x[x] = Range(101, 4000);
+        let total = input.count_accepted(&low_x).unwrap() + input.count_accepted(&high_x).unwrap();
+        assert_eq!(Some(total), input.count_accepted(&all));
+
+        let input = parse_input("in{x<101:A,R}\n\n{x=1,m=2,a=3,s=4}").unwrap();
+        assert_eq!(input.count_accepted(&all), Some(100 * 4000u64.pow(3)));
+        let mut bounds = all;
+        bounds[0] = Range(51, 200);
+        assert_eq!(input.count_accepted(&bounds), Some(50 * 4000u64.pow(3)));
+    }
+
+    #[test]
+    fn reports_loops() {
+        let input = "in{x<10:a,R}\na{m>5:in,A}\n\n{x=1,m=2,a=3,s=4}";
+        assert_eq!(
+            parse_input(input).unwrap_err(),
+            ParseError::new(19, 2, 1, "workflow `in` loops")
+        );
+    }
+
+    #[test]
+    fn reports_unknown_workflow() {
+        let input = "in{x<10:a,R}\na{m>5:b,A}\n\n{x=1,m=2,a=3,s=4}";
+        assert_eq!(
+            parse_input(input).unwrap_err(),
+            ParseError::new(19, 2, 1, "unknown workflow `b`")
+        );
+        let input = "a{m>5:R,A}\n\n{x=1,m=2,a=3,s=4}";
+        assert_eq!(
+            parse_input(input).unwrap_err(),
+            ParseError::new(19, 1, 1, "unknown workflow `in`")
+        );
+    }
+
+    #[test]
+    fn reports_duplicate_workflow() {
+        let input = "in{x<10:a,R}\na{m>5:R,A}\na{x>1:A,R}\n\n{x=1,m=2,a=3,s=4}";
+        assert_eq!(
+            parse_input(input).unwrap_err(),
+            ParseError::new(19, 3, 1, "duplicate workflow `a`")
+        );
+    }
+
+    #[test]
+    fn discovers_categories() {
+        let input = "in{q>5:A,x<3:A,R}\n\n{s=1,q=9,a=1,m=1,x=9}\n{x=2,m=1,a=1,s=1,q=1}";
+        let input = parse_input(input).unwrap();
+        assert_eq!(input.categories.0, ["q", "x", "s", "a", "m"]);
+        assert_eq!(part1(&input), 21 + 6);
+        assert_eq!(
+            input.count_accepted(&[Range(1, 10); 5]),
+            Some((5 * 10 + 5 * 2) * 10u64.pow(3))
+        );
+        assert_eq!(input.count_accepted(&[Range(1, 10); 4]), None);
+    }
+
+    #[test]
+    fn reports_missing_rating() {
+        let input = "in{x<10:A,R}\n\n{x=1,m=2}\n{x=1,m=2,m=3}";
+        assert_eq!(
+            parse_input(input).unwrap_err(),
+            ParseError::new(19, 4, 10, "duplicate rating")
+        );
+        let input = "in{x<10:A,R}\n\n{x=1,m=2}\n{x=1}";
+        assert_eq!(
+            parse_input(input).unwrap_err(),
+            ParseError::new(19, 4, 1, "missing rating `m`")
+        );
     }
-    res
 }
@@ -1,3 +1,4 @@
+use crate::util::error::*;
 use std::str::FromStr;
 
 #[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
@@ -35,11 +36,14 @@ impl Game {
 }
 
 impl FromStr for Game {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (id, rest) = s.strip_prefix("Game ").unwrap().split_once(": ").unwrap();
-        let id = id.parse::<u32>().unwrap();
+        let (id, rest) = s
+            .strip_prefix("Game ")
+            .and_then(|s| s.split_once(": "))
+            .ok_or_else(|| ParseError::at(2, s, s, "expected `Game <id>: `"))?;
+        let id = parse_token(2, s, id)?;
 
         let cubes = rest
             .split("; ")
@@ -48,27 +52,30 @@ impl FromStr for Game {
                 let mut red = 0;
                 let mut blue = 0;
                 for p in set.split(", ") {
-                    let (num, color) = p.split_once(' ').unwrap();
-                    let num = num.parse::<u32>().unwrap();
+                    let (num, color) = p
+                        .split_once(' ')
+                        .ok_or_else(|| ParseError::at(2, s, p, "expected `<count> <color>`"))?;
+                    let num = parse_token(2, s, num)?;
                     match color {
                         "red" => red = num,
                         "green" => green = num,
                         "blue" => blue = num,
-                        _ => unreachable!(),
+                        _ => return Err(ParseError::at(2, s, color, "unknown color")),
                     }
                 }
-                CubeSet { red, green, blue }
+                Ok(CubeSet { red, green, blue })
             })
-            .collect();
+            .collect::<Result<_, _>>()?;
         Ok(Game { id, cubes })
     }
 }
 
 #[aoc_generator(day2)]
-fn parse_input(input: &str) -> Vec<Game> {
+fn parse_input(input: &str) -> Result<Vec<Game>, ParseError> {
     input
         .lines()
-        .filter_map(|s| Game::from_str(s).ok())
+        .enumerate()
+        .map(|(i, s)| Game::from_str(s).map_err(|e| e.offset_lines(i)))
         .collect()
 }
 
@@ -90,3 +97,33 @@ fn part1(input: &[Game]) -> u32 {
 fn part2(input: &[Game]) -> u32 {
     input.iter().map(|game| game.minimum_set().power()).sum()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(part1(&parse_input(EXAMPLE).unwrap()), 8);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(part2(&parse_input(EXAMPLE).unwrap()), 2286);
+    }
+
+    #[test]
+    fn reports_bad_color() {
+        let input = "Game 1: 3 blue\nGame 2: 1 blue, 2 purple";
+        assert_eq!(
+            parse_input(input).unwrap_err(),
+            ParseError::new(2, 2, 19, "unknown color")
+        );
+    }
+}
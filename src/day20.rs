@@ -1,7 +1,7 @@
-use dyn_clone::DynClone;
+use crate::util::dot::*;
+use crate::util::error::*;
 use num::integer::lcm;
-use std::collections::{HashMap, VecDeque};
-use std::fmt::Debug;
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::ops::Not;
 
 #[derive(Debug, Copy, Clone, PartialEq, Hash, Default, Eq)]
@@ -21,163 +21,398 @@ impl Not for Pulse {
         }
     }
 }
-trait Module: Debug + DynClone {
-    fn recv(&mut self, signal: Pulse, sender: String);
-    fn get_state(&self) -> Pulse;
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Module {
+    Broadcaster,
+    FlipFlop {
+        on: bool,
+    },
+    /// Remembers the last pulse from each of its inputs.
+    Conjunction {
+        memory: BTreeMap<usize, Pulse>,
+    },
+    /// Never sends anything, such as `rx` or any other name that only appears as a destination.
+    Output,
+}
+
+impl Module {
+    /// How the module type is written in the puzzle input.
+    fn prefix(&self) -> &'static str {
+        match self {
+            Module::FlipFlop { .. } => "%",
+            Module::Conjunction { .. } => "&",
+            _ => "",
+        }
+    }
+
+    /// Handles a pulse, returning the pulse sent on to every output if there is one.
+    fn recv(&mut self, signal: Pulse, sender: usize) -> Option<Pulse> {
+        match self {
+            Module::Broadcaster => Some(signal),
+            Module::FlipFlop { .. } if signal == Pulse::High => None,
+            Module::FlipFlop { on } => {
+                *on = !*on;
+                Some(if *on { Pulse::High } else { Pulse::Low })
+            }
+            Module::Conjunction { memory } => {
+                memory.insert(sender, signal);
+                let all_high = memory.values().all(|&pulse| pulse == Pulse::High);
+                Some(if all_high { Pulse::Low } else { Pulse::High })
+            }
+            Module::Output => None,
+        }
+    }
 
-    fn initial(&mut self, _: String) {}
-    fn will_send(&self, _: Pulse) -> bool {
-        true
+    /// Appends whatever the module remembers between pulses.
+    fn snapshot(&self, out: &mut Vec<Pulse>) {
+        match self {
+            Module::FlipFlop { on } => out.push(if *on { Pulse::High } else { Pulse::Low }),
+            Module::Conjunction { memory } => out.extend(memory.values()),
+            _ => {}
+        }
     }
 }
 
-dyn_clone::clone_trait_object!(Module);
-#[derive(Debug, Copy, Clone, Default)]
-struct FlipFlop {
-    state: Pulse,
+/// Pulses sent during a single button press, the press itself included.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct PressReport {
+    low: u64,
+    high: u64,
+    /// Every pulse in the order it was sent as `(from, to, pulse)`, only kept when tracing.
+    trace: Vec<(usize, usize, Pulse)>,
 }
 
-impl Module for FlipFlop {
-    fn recv(&mut self, signal: Pulse, _: String) {
-        if signal == Pulse::Low {
-            self.state = !self.state;
-        }
+/// The memory of every flip-flop and conjunction, equal snapshots mean the network is in the same state.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Snapshot(Vec<Pulse>);
+
+// the button and the broadcaster always get the first two ids, the button only ever sends
+const BUTTON: usize = 0;
+const BROADCASTER: usize = 1;
+
+/// The modules of the input and the wires between them, each module known by its id.
+#[derive(Debug, Clone)]
+pub struct Network {
+    names: Vec<String>,
+    modules: Vec<Module>,
+    inputs: Vec<Vec<usize>>,
+    outputs: Vec<Vec<usize>>,
+    presses: u64,
+    tracing: bool,
+}
+
+impl Network {
+    pub fn id(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|n| n == name)
     }
 
-    fn get_state(&self) -> Pulse {
-        self.state
+    pub fn name(&self, id: usize) -> &str {
+        &self.names[id]
     }
 
-    fn will_send(&self, signal: Pulse) -> bool {
-        signal == Pulse::Low
+    fn module(&self, id: usize) -> &Module {
+        &self.modules[id]
     }
-}
-#[derive(Debug, Copy, Clone, Default)]
-struct Broadcaster {
-    state: Pulse,
-}
 
-impl Module for Broadcaster {
-    fn recv(&mut self, signal: Pulse, _: String) {
-        self.state = signal;
+    /// Modules sending pulses to `id`.
+    pub fn inputs(&self, id: usize) -> &[usize] {
+        &self.inputs[id]
     }
 
-    fn get_state(&self) -> Pulse {
-        self.state
+    /// Modules `id` sends pulses to.
+    pub fn outputs(&self, id: usize) -> &[usize] {
+        &self.outputs[id]
     }
-}
 
-#[derive(Default, Clone, Debug)]
-struct Conjunction {
-    inputs: HashMap<String, Pulse>,
-}
+    fn snapshot(&self) -> Snapshot {
+        let mut state = Vec::new();
+        for module in &self.modules {
+            module.snapshot(&mut state);
+        }
+        Snapshot(state)
+    }
 
-impl Module for Conjunction {
-    fn recv(&mut self, signal: Pulse, sender: String) {
-        self.inputs.insert(sender, signal);
+    fn press(&mut self) -> PressReport {
+        self.presses += 1;
+        let mut report = PressReport::default();
+        let mut queue = VecDeque::from([(BUTTON, BROADCASTER, Pulse::Low)]);
+        while let Some((from, to, signal)) = queue.pop_front() {
+            match signal {
+                Pulse::Low => report.low += 1,
+                Pulse::High => report.high += 1,
+            }
+            if self.tracing {
+                report.trace.push((from, to, signal));
+            }
+            let Some(sent) = self.modules[to].recv(signal, from) else {
+                continue;
+            };
+            queue.extend(self.outputs(to).iter().map(|&next| (to, next, sent)));
+        }
+        report
     }
 
-    fn get_state(&self) -> Pulse {
-        if self.inputs.iter().all(|(_, &signal)| signal == Pulse::High) {
-            Pulse::Low
-        } else {
-            Pulse::High
+    /// Presses the button until `done` holds, returning how many presses that took in total.
+    fn run_until(
+        &mut self,
+        max_presses: u64,
+        mut done: impl FnMut(&Self, &PressReport) -> bool,
+    ) -> Option<u64> {
+        while self.presses < max_presses {
+            let report = self.press();
+            if done(self, &report) {
+                return Some(self.presses);
+            }
         }
+        None
     }
 
-    fn initial(&mut self, sender: String) {
-        self.inputs.insert(sender, Pulse::Low);
+    /// The module network in Graphviz DOT format, with untyped destinations such as `rx` highlighted.
+    fn to_dot(&self) -> String {
+        let mut dot = Dot::new();
+        for (id, name) in self.names.iter().enumerate().skip(1) {
+            let module = &self.modules[id];
+            let label = format!("{}{name}", module.prefix());
+            dot.node(name, label, *module == Module::Output);
+            for &dest in self.outputs(id) {
+                dot.edge(name, self.name(dest), "", false);
+            }
+        }
+        dot.finish()
     }
 }
-#[derive(Clone)]
-struct Input {
-    modules: HashMap<String, Box<dyn Module>>,
-    neighbors: HashMap<String, Vec<String>>,
+
+/// The module network of the input, e.g. to follow the wires into `rx`.
+pub fn network(input: &str) -> Result<Network, ParseError> {
+    parse_input(input)
+}
+
+/// The module network of the input in Graphviz DOT format.
+pub fn to_dot(input: &str) -> Result<String, ParseError> {
+    Ok(parse_input(input)?.to_dot())
 }
 
 #[aoc_generator(day20)]
-fn parse_input(input: &str) -> Input {
-    let mut modules = HashMap::new();
-    let mut neighbors = HashMap::new();
+fn parse_input(input: &str) -> Result<Network, ParseError> {
+    let mut network = Network {
+        names: vec!["button".to_string(), "broadcaster".to_string()],
+        modules: vec![Module::Output, Module::Output],
+        inputs: vec![Vec::new(), vec![BUTTON]],
+        outputs: vec![vec![BROADCASTER], Vec::new()],
+        presses: 0,
+        tracing: false,
+    };
+    let intern = |network: &mut Network, name: &str| {
+        network.id(name).unwrap_or_else(|| {
+            network.names.push(name.to_string());
+            network.modules.push(Module::Output);
+            network.inputs.push(Vec::new());
+            network.outputs.push(Vec::new());
+            network.names.len() - 1
+        })
+    };
+
     for line in input.lines() {
-        let (module, dests) = line.split_once(" -> ").unwrap();
-        let (module, name): (Box<dyn Module>, String) = match module.split_at(1) {
-            ("%", name) => (Box::<FlipFlop>::default(), name.trim().to_string()),
-            ("&", name) => (Box::<Conjunction>::default(), name.trim().to_string()),
-            ("b", _) => (Box::<Broadcaster>::default(), "broadcaster".to_string()),
-            _ => unreachable!("Malformed input"),
+        let (module, dests) = line.split_once(" -> ").ok_or_else(|| {
+            ParseError::at(20, input, line, "expected `<module> -> <destinations>`")
+        })?;
+        let (module, name) = match module.split_at_checked(1) {
+            Some(("%", name)) => (Module::FlipFlop { on: false }, name.trim()),
+            Some(("&", name)) => (
+                Module::Conjunction {
+                    memory: BTreeMap::new(),
+                },
+                name.trim(),
+            ),
+            _ if module == "broadcaster" => (Module::Broadcaster, module),
+            _ => return Err(ParseError::at(20, input, module, "unknown module type")),
         };
-        modules.insert(name.clone(), module);
-        let neigh = dests.split(", ").map(ToString::to_string).collect();
-        neighbors.insert(name, neigh);
-    }
-    for (name, dests) in &neighbors {
-        for dest in dests {
-            if let Some(module) = modules.get_mut(dest) {
-                module.initial(name.to_string());
+        let id = intern(&mut network, name);
+        if network.modules[id] != Module::Output {
+            let message = format!("duplicate module `{name}`");
+            return Err(ParseError::at(20, input, name, message));
+        }
+        network.modules[id] = module;
+        network.outputs[id] = dests
+            .split(", ")
+            .map(|dest| intern(&mut network, dest))
+            .collect();
+    }
+    if network.modules[BROADCASTER] != Module::Broadcaster {
+        return Err(ParseError::new(20, 1, 1, "no broadcaster module"));
+    }
+    for from in 0..network.names.len() {
+        for &dest in &network.outputs[from] {
+            network.inputs[dest].push(from);
+            if let Module::Conjunction { memory } = &mut network.modules[dest] {
+                memory.insert(from, Pulse::Low);
             }
         }
     }
-    Input { modules, neighbors }
+    Ok(network)
 }
 
-#[aoc(day20, part1)]
-fn part1(input: &Input) -> u64 {
-    let mut modules = input.modules.clone();
-    let mut high_count = 0;
-    let mut low_count = 0;
-
-    for _ in 0..1000 {
-        low_count += 1;
-        let mut frontier = VecDeque::new();
-        frontier.push_back(("broadcaster".to_string(), Pulse::Low));
-
-        while let Some((name, signal)) = frontier.pop_front() {
-            let neighbors = input.neighbors.get(&name).unwrap();
-            match signal {
-                Pulse::Low => low_count += neighbors.len() as u64,
-                Pulse::High => high_count += neighbors.len() as u64,
-            };
+const PRESSES: u64 = 1000;
 
-            for n in neighbors {
-                if let Some(module) = modules.get_mut(n) {
-                    if !module.will_send(signal) {
-                        continue;
-                    }
-                    module.recv(signal, name.clone());
-                    frontier.push_back((n.to_string(), module.get_state()));
-                }
-            }
-        }
+#[aoc(day20, part1)]
+fn part1(input: &Network) -> u64 {
+    let mut network = input.clone();
+    let initial = network.snapshot();
+    let (mut low, mut high) = (0, 0);
+    network.run_until(PRESSES, |network, report| {
+        low += report.low;
+        high += report.high;
+        network.snapshot() == initial
+    });
+    // back where it started, so the pulses repeat until the presses run out
+    let cycle = network.presses;
+    let (mut low, mut high) = (low * (PRESSES / cycle), high * (PRESSES / cycle));
+    for _ in 0..PRESSES % cycle {
+        let report = network.press();
+        low += report.low;
+        high += report.high;
     }
-
-    high_count * low_count
+    low * high
 }
 
+const PRESS_BUDGET: u64 = 1 << 16;
+
 #[aoc(day20, part2)]
-fn part2(input: &Input) -> u64 {
-    let mut modules = input.modules.clone();
-    let mut cycle = Vec::new();
-    for i in 0..4096 {
-        let mut frontier = VecDeque::new();
-        frontier.push_back(("broadcaster".to_string(), Pulse::Low));
-        while let Some((name, signal)) = frontier.pop_front() {
-            for n in input.neighbors.get(&name).unwrap() {
-                if n == "rx" || n == "th" {
-                    if signal == Pulse::High {
-                        cycle.push(i + 1u64);
-                    }
-                    continue;
-                }
-                if let Some(module) = modules.get_mut(n) {
-                    if !module.will_send(signal) {
-                        continue;
-                    }
-                    module.recv(signal, name.clone());
-                    frontier.push_back((n.to_string(), module.get_state()));
+fn part2(input: &Network) -> Result<u64, String> {
+    let rx = input.id("rx").ok_or("no rx module")?;
+    let [hub] = input.inputs(rx)[..] else {
+        return Err("Expected exactly one module feeding rx".to_string());
+    };
+    // rx only gets a low pulse once every source has just sent the hub a high one
+    if !matches!(input.module(hub), Module::Conjunction { .. }) {
+        return Err(format!("{} should be a conjunction", input.name(hub)));
+    }
+    let sources = input.inputs(hub).to_vec();
+    // presses on which each source sent a high pulse to the hub
+    let mut hits: HashMap<usize, Vec<u64>> = sources.iter().map(|&s| (s, Vec::new())).collect();
+
+    let mut network = input.clone();
+    network.tracing = true;
+    network.run_until(PRESS_BUDGET, |network, report| {
+        for &(from, to, signal) in &report.trace {
+            if to == hub && signal == Pulse::High {
+                let presses = hits.get_mut(&from).unwrap();
+                if presses.last() != Some(&network.presses) {
+                    presses.push(network.presses);
                 }
             }
         }
+        hits.values().all(|presses| presses.len() >= 2)
+    });
+
+    sources
+        .into_iter()
+        .try_fold(1, |acc, source| match hits[&source][..] {
+            [first, second, ..] if second - first == first => Ok(lcm(acc, first)),
+            [first, second, ..] => Err(format!(
+                "{} first fires on press {first} but repeats every {} presses",
+                input.name(source),
+                second - first
+            )),
+            _ => Err(format!(
+                "{} did not cycle within {PRESS_BUDGET} presses",
+                input.name(source)
+            )),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE1: &str = "broadcaster -> a, b, c
+%a -> b
+%b -> c
+%c -> inv
+&inv -> a";
+
+    const EXAMPLE2: &str = "broadcaster -> a
+%a -> inv, con
+&inv -> b
+%b -> con
+&con -> output";
+
+    // two counters firing every 2 and every 4 presses into the conjunction feeding rx
+    const COUNTERS: &str = "broadcaster -> a, b1
+%a -> ia
+&ia -> hub
+%b1 -> b2
+%b2 -> ib
+&ib -> hub
+&hub -> rx";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(part1(&parse_input(EXAMPLE1).unwrap()), 32_000_000);
+        assert_eq!(part1(&parse_input(EXAMPLE2).unwrap()), 11_687_500);
+    }
+
+    #[test]
+    fn part2_counters() {
+        assert_eq!(part2(&parse_input(COUNTERS).unwrap()), Ok(4));
+    }
+
+    #[test]
+    fn part2_requires_single_feeder() {
+        let input = parse_input("broadcaster -> a\n%a -> rx, b\n%b -> rx").unwrap();
+        assert!(part2(&input).is_err());
+    }
+
+    #[test]
+    fn reports_duplicate_module() {
+        let input = "broadcaster -> a\n%a -> b\n&b -> a\n%b -> a";
+        assert_eq!(
+            parse_input(input).err(),
+            Some(ParseError::new(20, 4, 2, "duplicate module `b`"))
+        );
+    }
+
+    #[test]
+    fn dot_export() {
+        let dot = parse_input(EXAMPLE2).unwrap().to_dot();
+        assert!(dot.contains("\"a\" [label=\"%a\"];"));
+        assert!(dot.contains("\"con\" [label=\"&con\"];"));
+        assert!(dot.contains("\"output\" [label=\"output\", color=red, penwidth=2];"));
+        assert_eq!(dot.matches("->").count(), 6);
+    }
+
+    #[test]
+    fn press_report_and_trace() {
+        let mut network = parse_input(EXAMPLE1).unwrap();
+        network.tracing = true;
+        let report = network.press();
+        assert_eq!((report.low, report.high), (8, 4));
+        let (from, to, signal) = report.trace[1];
+        assert_eq!(
+            (network.name(from), network.name(to), signal),
+            ("broadcaster", "a", Pulse::Low)
+        );
+        assert_eq!(report.trace.len(), 12);
+    }
+
+    #[test]
+    fn state_cycles() {
+        let mut network = parse_input(EXAMPLE2).unwrap();
+        let initial = network.snapshot();
+        let cycle = network.run_until(100, |network, _| network.snapshot() == initial);
+        assert_eq!(cycle, Some(4));
+    }
+
+    #[test]
+    fn module_kinds() {
+        let network = network(COUNTERS).unwrap();
+        let id = |name| network.id(name).unwrap();
+        assert_eq!(*network.module(id("rx")), Module::Output);
+        assert_eq!(*network.module(id("a")), Module::FlipFlop { on: false });
+        assert!(
+            matches!(network.module(id("hub")), Module::Conjunction { memory } if memory.len() == 2)
+        );
+        assert_eq!(network.inputs(id("hub")), [id("ia"), id("ib")]);
+        assert_eq!(network.outputs(BROADCASTER), [id("a"), id("b1")]);
     }
-    cycle.into_iter().reduce(lcm).unwrap()
 }
@@ -1,40 +1,114 @@
-use itertools::Itertools;
+use crate::util::error::*;
+use crate::util::grid::*;
+use crate::util::point::*;
+use crate::util::search::*;
 use num::Rational64;
-use std::collections::HashSet;
 
-const START: (usize, usize) = (65, 65);
-const STEPS: u32 = 64;
-#[aoc_generator(day21)]
-fn parse_input(input: &str) -> Vec<Vec<char>> {
-    input
-        .replace('S', ".")
-        .lines()
-        .map(|s| s.chars().collect())
-        .collect()
+const STEPS: usize = 64;
+const TILED_STEPS: usize = 26_501_365;
+
+/// Whether the garden is walled in or repeats in every direction.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Tiling {
+    Bounded,
+    Infinite,
 }
 
-#[aoc(day21, part1)]
-fn part1(input: &[Vec<char>]) -> usize {
-    let rows = input.len();
-    let cols = input[0].len();
-    let mut visited = HashSet::new();
-    visited.insert(START);
-    for _ in 0..STEPS {
-        visited = visited
+#[derive(Clone)]
+struct GardenWalk {
+    grid: Grid<u8>,
+    start: Point,
+}
+
+impl GardenWalk {
+    fn is_open(&self, pos: Point, tiling: Tiling) -> bool {
+        match tiling {
+            Tiling::Bounded => self.grid.contains(pos) && self.grid[pos] != b'#',
+            Tiling::Infinite => self.grid.tiled()[pos] != b'#',
+        }
+    }
+
+    fn reachable_after(&self, steps: usize, tiling: Tiling) -> usize {
+        if tiling == Tiling::Infinite && self.has_quadratic_growth() {
+            let period = self.grid.width as usize;
+            // reachable plots grow quadratically every time the walk crosses another tile
+            let inputs = (1..=3)
+                .map(|i| steps % period + i * period)
+                .collect::<Vec<_>>();
+            if steps > inputs[2] {
+                let outputs = inputs
+                    .iter()
+                    .map(|&x| self.walk(x, tiling))
+                    .collect::<Vec<_>>();
+                return lagrange_poly(&inputs, &outputs, steps);
+            }
+        }
+        self.walk(steps, tiling)
+    }
+
+    // square garden with the start dead centre and clear lines running through it and around the edge
+    fn has_quadratic_growth(&self) -> bool {
+        let Grid { width, height, .. } = self.grid;
+        let Point { x: mid_x, y: mid_y } = self.start;
+        let mut lines = (0..width)
+            .flat_map(|x| [mid_y, 0, height - 1].map(|y| Point::new(x, y)))
+            .chain((0..height).flat_map(|y| [mid_x, 0, width - 1].map(|x| Point::new(x, y))));
+
+        width == height
+            && self.start == Point::new(width / 2, height / 2)
+            && lines.all(|pos| self.grid[pos] != b'#')
+    }
+
+    fn walk(&self, steps: usize, tiling: Tiling) -> usize {
+        let plots = Plots {
+            garden: self,
+            tiling,
+        };
+        // a plot first reached with the right parity can be revisited by stepping back and forth
+        bfs_layers(&plots, self.start)
+            .take(steps + 1)
+            .enumerate()
+            .filter(|(i, _)| i & 1 == steps & 1)
+            .map(|(_, layer)| layer.len())
+            .sum()
+    }
+}
+
+struct Plots<'a> {
+    garden: &'a GardenWalk,
+    tiling: Tiling,
+}
+
+impl Graph for Plots<'_> {
+    type Node = Point;
+
+    fn successors(&self, &pos: &Point) -> impl IntoIterator<Item = (Point, u64)> {
+        ORTHOGONAL
+            .map(|dir| pos + dir)
             .into_iter()
-            .flat_map(|(row, col)| {
-                [
-                    (row.saturating_sub(1), col),
-                    (row, col + 1),
-                    (row + 1, col),
-                    (row, col.saturating_sub(1)),
-                ]
-                .into_iter()
-                .filter(|&(row, col)| row < rows && col < cols && input[row][col] == '.')
-            })
-            .collect::<HashSet<_>>();
+            .filter(|&next| self.garden.is_open(next, self.tiling))
+            .map(|next| (next, 1))
     }
-    visited.len()
+}
+
+#[aoc_generator(day21)]
+fn parse_input(input: &str) -> Result<GardenWalk, ParseError> {
+    validate_grid(21, input, input, ".#S")?;
+    let grid = Grid::parse(input);
+    let start = grid
+        .find(b'S')
+        .ok_or_else(|| ParseError::new(21, 1, 1, "no start tile"))?;
+    Ok(GardenWalk { grid, start })
+}
+
+/// Garden plots in the input the elf can be standing on after exactly `steps` steps.
+pub fn reachable_after(input: &str, steps: usize, tiling: Tiling) -> Result<usize, ParseError> {
+    Ok(parse_input(input)?.reachable_after(steps, tiling))
+}
+
+#[aoc(day21, part1)]
+fn part1(input: &GardenWalk) -> usize {
+    input.reachable_after(STEPS, Tiling::Bounded)
 }
 
 fn lagrange_poly(inputs: &[usize], outputs: &[usize], point: usize) -> usize {
@@ -64,7 +138,7 @@ fn lagrange_poly(inputs: &[usize], outputs: &[usize], point: usize) -> usize {
             })
             .collect()
     }
-    let basis = make_basis(&inputs, point);
+    let basis = make_basis(inputs, point);
 
     basis
         .iter()
@@ -74,44 +148,69 @@ fn lagrange_poly(inputs: &[usize], outputs: &[usize], point: usize) -> usize {
         .to_integer() as usize
 }
 
-fn walk(grid: &[Vec<char>], steps: usize) -> usize {
-    let rows = grid.len() as i64;
-    let cols = grid[0].len() as i64;
-    let start = (START.0 as i64, START.1 as i64);
-    let mut frontier = HashSet::from([start]);
-    let mut visited = HashSet::new();
-    let mut reachable = HashSet::new();
-    for i in 0..=steps {
-        let mut new_frontier = HashSet::new();
-        for (row, col) in frontier {
-            visited.insert((row, col));
-            if i & 1 == steps & 1 {
-                reachable.insert((row, col));
-            }
-            for (dr, dc) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
-                let new_row = row + dr;
-                let new_col = col + dc;
-
-                if grid[new_row.rem_euclid(rows) as usize][new_col.rem_euclid(cols) as usize] == '#'
-                {
-                    continue;
-                }
-                if visited.contains(&(new_row, new_col)) {
-                    continue;
-                }
-                new_frontier.insert((new_row, new_col));
-            }
-        }
-        frontier = new_frontier;
-    }
-    reachable.len()
+#[aoc(day21, part2)]
+fn part2(input: &GardenWalk) -> usize {
+    input.reachable_after(TILED_STEPS, Tiling::Infinite)
 }
 
-#[aoc(day21, part2)]
-fn part2(input: &[Vec<char>]) -> usize {
-    let rows = input.len();
-    let inputs = (1..=5).step_by(2).map(|i| i * rows / 2).collect::<Vec<_>>();
-    let outputs = inputs.iter().map(|&x| walk(input, x)).collect::<Vec<_>>();
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "...........
+.....###.#.
+.###.##..#.
+..#.#...#..
+....#.#....
+.##..S####.
+.##..#...#.
+.......##..
+.##.#.####.
+.##..##.##.
+...........";
+
+    // same garden with the start row and column cleared so reachable plots grow quadratically
+    const OPEN: &str = "...........
+......##.#.
+.###..#..#.
+..#.#...#..
+....#.#....
+.....S.....
+.##......#.
+.......##..
+.##.#.####.
+.##...#.##.
+...........";
+
+    #[test]
+    fn bounded_example() {
+        assert_eq!(
+            parse_input(EXAMPLE)
+                .unwrap()
+                .reachable_after(6, Tiling::Bounded),
+            16
+        );
+        assert_eq!(reachable_after(EXAMPLE, 6, Tiling::Bounded), Ok(16));
+    }
 
-    lagrange_poly(&inputs, &outputs, 26_501_365)
+    #[test]
+    fn infinite_example() {
+        let garden = parse_input(EXAMPLE).unwrap();
+        assert!(!garden.has_quadratic_growth());
+        for (steps, plots) in [(6, 16), (10, 50), (50, 1594), (100, 6536), (500, 167_004)] {
+            assert_eq!(garden.reachable_after(steps, Tiling::Infinite), plots);
+        }
+    }
+
+    #[test]
+    fn quadratic_matches_walk() {
+        let garden = parse_input(OPEN).unwrap();
+        assert!(garden.has_quadratic_growth());
+        for steps in [100, 201, 333, 500] {
+            assert_eq!(
+                garden.reachable_after(steps, Tiling::Infinite),
+                garden.walk(steps, Tiling::Infinite)
+            );
+        }
+    }
 }
@@ -1,3 +1,6 @@
+use crate::util::error::*;
+use crate::util::grid::*;
+use crate::util::point::{Point, Point3};
 use itertools::Itertools;
 use std::collections::VecDeque;
 
@@ -6,54 +9,77 @@ struct Input {
     below: Vec<Vec<usize>>,
 }
 
-fn parse_bricks(input: &str) -> Vec<[usize; 6]> {
-    input
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Brick {
+    min: Point3,
+    max: Point3,
+}
+
+impl Brick {
+    // the cells it covers seen from above
+    fn footprint(self) -> impl Iterator<Item = Point> {
+        (self.min.x..=self.max.x)
+            .cartesian_product(self.min.y..=self.max.y)
+            .map(|(x, y)| Point::new(x, y))
+    }
+}
+
+fn parse_bricks(input: &str) -> Result<Vec<Brick>, ParseError> {
+    let mut bricks = input
         .lines()
         .map(|line| {
-            let line = line.replace('~', ",");
-            line.split(',')
-                .filter_map(|s| s.parse().ok())
-                .collect_vec()
+            let [x1, y1, z1, x2, y2, z2]: [i32; 6] = line
+                .split(['~', ','])
+                .map(|s| parse_token(22, input, s))
+                .collect::<Result<Vec<_>, _>>()?
                 .try_into()
-                .unwrap()
+                .map_err(|_| ParseError::at(22, input, line, "expected `x,y,z~x,y,z`"))?;
+            let (min, max) = (Point3::new(x1, y1, z1), Point3::new(x2, y2, z2));
+            if min.x > max.x || min.y > max.y || min.z > max.z || min.z < 1 {
+                return Err(ParseError::at(22, input, line, "malformed brick extent"));
+            }
+            if min.x < 0 || min.y < 0 || max.x >= 10 || max.y >= 10 {
+                return Err(ParseError::at(
+                    22,
+                    input,
+                    line,
+                    "brick lies outside the 10x10 footprint",
+                ));
+            }
+            Ok(Brick { min, max })
         })
-        .sorted_unstable_by_key(|brick: &[usize; 6]| brick[2])
-        .collect()
+        .collect::<Result<Vec<_>, _>>()?;
+    bricks.sort_unstable_by_key(|brick| brick.min.z);
+    Ok(bricks)
 }
 #[aoc_generator(day22)]
-fn parse_input(input: &str) -> Input {
-    let bricks = parse_bricks(input);
-    let mut heights = [[0; 10]; 10];
-    let mut indices = [[usize::MAX; 10]; 10];
+fn parse_input(input: &str) -> Result<Input, ParseError> {
+    let bricks = parse_bricks(input)?;
+    let mut heights = Grid::new(10, 10, 0);
+    let mut indices = Grid::new(10, 10, usize::MAX);
     let mut above = vec![vec![]; bricks.len()];
     let mut below = vec![vec![]; bricks.len()];
 
-    for (i, [x1, y1, z1, x2, y2, z2]) in bricks.into_iter().enumerate() {
-        let height = z2 - z1 + 1;
-        let top = (x1..=x2)
-            .cartesian_product(y1..=y2)
-            .map(|(x, y)| heights[x][y])
-            .max()
-            .unwrap_or(0);
+    for (i, brick) in bricks.into_iter().enumerate() {
+        let height = brick.max.z - brick.min.z + 1;
+        let top = brick.footprint().map(|pos| heights[pos]).max().unwrap_or(0);
         let mut prev = usize::MAX;
 
-        for x in x1..=x2 {
-            for y in y1..=y2 {
-                if heights[x][y] == top {
-                    let index = indices[x][y];
-                    if index != prev {
-                        above[index].push(i);
-                        below[i].push(index);
-                        prev = index;
-                    }
+        for pos in brick.footprint() {
+            if heights[pos] == top {
+                let index = indices[pos];
+                if index != prev {
+                    above[index].push(i);
+                    below[i].push(index);
+                    prev = index;
                 }
-
-                heights[x][y] = top + height;
-                indices[x][y] = i;
             }
+
+            heights[pos] = top + height;
+            indices[pos] = i;
         }
     }
-    Input { above, below }
+    Ok(Input { above, below })
 }
 
 fn safe_to_remove(supports: &[Vec<usize>]) -> Vec<bool> {
@@ -103,3 +129,34 @@ fn part2(input: &Input) -> usize {
     }
     res
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "1,0,1~1,2,1
+0,0,2~2,0,2
+0,2,3~2,2,3
+0,0,4~0,2,4
+2,0,5~2,2,5
+0,1,6~2,1,6
+1,1,8~1,1,9";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(part1(&parse_input(EXAMPLE).unwrap()), 5);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(part2(&parse_input(EXAMPLE).unwrap()), 7);
+    }
+
+    #[test]
+    fn rejects_bricks_outside_footprint() {
+        assert_eq!(
+            parse_bricks("1,0,1~1,2,1\n-1,0,2~2,0,2").unwrap_err(),
+            ParseError::new(22, 2, 1, "brick lies outside the 10x10 footprint")
+        );
+    }
+}
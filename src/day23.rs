@@ -1,38 +1,236 @@
+use crate::util::dot::*;
+use crate::util::error::*;
 use crate::util::grid::*;
 use crate::util::point::*;
 use fxhash::FxHashSet;
 use itertools::Itertools;
+use rayon::prelude::*;
 use std::collections::HashMap;
 
-const START: Point = Point::new(1, 0);
-const END: Point = Point::new(139, 140);
 type Graph = HashMap<Point, Vec<(usize, Point)>>;
-fn contract_graph(mut graph: Graph) -> Graph {
-    let corridors = graph
-        .iter()
-        .filter(|(_, adj)| adj.len() == 2)
-        .map(|(&pos, _)| pos)
-        .collect_vec();
 
-    for pos in corridors {
-        let [(d1, p1), (d2, p2)] = graph.remove(&pos).unwrap()[..] else {
-            unreachable!()
+// rough number of partial hikes to hand out to the thread pool
+const SPLIT_TASKS: usize = 256;
+
+/// The contracted graph with junctions renumbered densely, so a set of them fits in a `u64`.
+#[derive(Debug)]
+struct Trail {
+    nodes: Vec<Point>,
+    edges: Vec<Vec<(usize, u8)>>,
+    start: u8,
+    end: u8,
+    // a hike reaching this junction has to head straight for the exit, which is this far
+    last_junction: u8,
+    last_leg: usize,
+}
+
+impl Trail {
+    fn new(grid: &Grid<u8>, graph: Graph) -> Result<Self, ParseError> {
+        let start = find_opening(grid, 0)?;
+        let end = find_opening(grid, grid.height - 1)?;
+        let graph = contract_graph(graph);
+        let nodes = graph
+            .keys()
+            .copied()
+            .sorted_by_key(|p| (p.y, p.x))
+            .collect_vec();
+        if nodes.len() > 64 {
+            return Err(ParseError::new(
+                23,
+                1,
+                1,
+                format!("expected at most 64 junctions, found {}", nodes.len()),
+            ));
+        }
+        let ids: HashMap<Point, u8> = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, &p)| (p, i as u8))
+            .collect();
+        let id = |pos: &Point| ids[pos];
+        let edges = nodes
+            .iter()
+            .map(|pos| {
+                graph[pos]
+                    .iter()
+                    .map(|(dist, next)| (*dist, id(next)))
+                    .collect_vec()
+            })
+            .collect_vec();
+        let (start, end) = (id(&start), id(&end));
+
+        let into_end = (0..nodes.len())
+            .flat_map(|from| edges[from].iter().map(move |&(dist, to)| (from, dist, to)))
+            .filter(|&(_, _, to)| to == end)
+            .collect_vec();
+        let (last_junction, last_leg) = match into_end[..] {
+            [(from, dist, _)] => (from as u8, dist),
+            _ => (end, 0),
         };
+        Ok(Self {
+            nodes,
+            edges,
+            start,
+            end,
+            last_junction,
+            last_leg,
+        })
+    }
+
+    fn longest_hike(&self) -> Option<usize> {
+        self.search().map(|(dist, _)| dist)
+    }
+
+    // length of the longest hike along with the junctions it passes through in order
+    fn search(&self) -> Option<(usize, Vec<u8>)> {
+        let mut tasks = vec![(vec![self.start], 1u64 << self.start, 0)];
+        // breadth-first until there is enough work to share, finished hikes stay in the list
+        while tasks.len() < SPLIT_TASKS {
+            let mut next_tasks = Vec::new();
+            let mut expanded = false;
+            for (path, visited, dist) in tasks {
+                let node = path[path.len() - 1];
+                if node == self.last_junction {
+                    next_tasks.push((path, visited, dist));
+                    continue;
+                }
+                for &(edge, next) in &self.edges[node as usize] {
+                    if visited & (1 << next) == 0 {
+                        let mut path = path.clone();
+                        path.push(next);
+                        next_tasks.push((path, visited | 1 << next, dist + edge));
+                        expanded = true;
+                    }
+                }
+            }
+            tasks = next_tasks;
+            if !expanded {
+                break;
+            }
+        }
+        tasks
+            .into_par_iter()
+            .filter_map(|(mut path, visited, dist)| {
+                let mut longest = None;
+                self.dfs(&mut path, visited, dist, &mut longest);
+                longest
+            })
+            .max_by_key(|&(dist, _)| dist)
+    }
 
-        let n1 = graph.get_mut(&p1).unwrap();
-        if let Some(i) = n1.iter().position(|(_, neighbor)| *neighbor == pos) {
-            n1[i] = (d1 + d2, p2);
+    // tries every way on from the end of `path`, keeping the longest finished hike
+    fn dfs(
+        &self,
+        path: &mut Vec<u8>,
+        visited: u64,
+        dist: usize,
+        longest: &mut Option<(usize, Vec<u8>)>,
+    ) {
+        let node = path[path.len() - 1];
+        if node == self.last_junction {
+            let dist = dist + self.last_leg;
+            if longest.as_ref().is_none_or(|&(best, _)| dist > best) {
+                let mut hike = path.clone();
+                if node != self.end {
+                    hike.push(self.end);
+                }
+                *longest = Some((dist, hike));
+            }
+            return;
+        }
+        for &(edge, next) in &self.edges[node as usize] {
+            if visited & (1 << next) == 0 {
+                path.push(next);
+                self.dfs(path, visited | 1 << next, dist + edge, longest);
+                path.pop();
+            }
         }
+    }
+
+    /// The junction graph in Graphviz DOT format with the longest hike highlighted.
+    fn to_dot(&self) -> String {
+        let hike = self.search().map(|(_, hike)| hike).unwrap_or_default();
+        let on_hike = |from: u8, to: u8| hike.windows(2).any(|pair| pair == [from, to]);
 
-        let n2 = graph.get_mut(&p2).unwrap();
-        if let Some(i) = n2.iter().position(|(_, neighbor)| *neighbor == pos) {
-            n2[i] = (d1 + d2, p1);
+        let mut dot = Dot::new();
+        for (id, pos) in self.nodes.iter().enumerate() {
+            dot.node(
+                id,
+                format!("{},{}", pos.x, pos.y),
+                hike.contains(&(id as u8)),
+            );
+        }
+        for (from, edges) in self.edges.iter().enumerate() {
+            for &(dist, to) in edges {
+                dot.edge(from, to, dist, on_hike(from as u8, to));
+            }
         }
+        dot.finish()
     }
+}
+
+fn find_opening(grid: &Grid<u8>, y: i32) -> Result<Point, ParseError> {
+    let openings = (0..grid.width)
+        .map(|x| Point::new(x, y))
+        .filter(|&pos| grid[pos] != b'#')
+        .collect_vec();
+    match openings[..] {
+        [pos] => Ok(pos),
+        _ => Err(ParseError::new(
+            23,
+            y as usize + 1,
+            1,
+            format!("expected exactly one opening, found {}", openings.len()),
+        )),
+    }
+}
+
+// Replaces every stretch of corridor with a single edge between the junctions at either end.
+// Edges may be one-way, so a corridor cell is any cell touching exactly two others.
+fn contract_graph(graph: Graph) -> Graph {
+    let mut touching: HashMap<Point, FxHashSet<Point>> = HashMap::new();
+    for (&pos, adj) in &graph {
+        for &(_, next) in adj {
+            touching.entry(pos).or_default().insert(next);
+            touching.entry(next).or_default().insert(pos);
+        }
+    }
+    let is_corridor = |pos: &Point| touching.get(pos).is_some_and(|t| t.len() == 2);
+
     graph
+        .iter()
+        .filter(|(pos, _)| !is_corridor(pos))
+        .map(|(&pos, adj)| {
+            let edges = adj
+                .iter()
+                .filter_map(|&(mut dist, mut next)| {
+                    let mut prev = pos;
+                    while is_corridor(&next) {
+                        // a slope pointing back the way we came leaves a dead end
+                        let &(step, after) = graph[&next].iter().find(|&&(_, p)| p != prev)?;
+                        dist += step;
+                        (prev, next) = (next, after);
+                    }
+                    Some((dist, next))
+                })
+                .collect();
+            (pos, edges)
+        })
+        .collect()
 }
+/// The junction graph of the input in Graphviz DOT format, with or without slopes.
+pub fn to_dot(input: &str, slopes: bool) -> Result<String, ParseError> {
+    let trail = if slopes {
+        parse_input1(input)?
+    } else {
+        parse_input2(input)?
+    };
+    Ok(trail.to_dot())
+}
+
 #[aoc_generator(day23, part1)]
-fn parse_input1(input: &str) -> Graph {
+fn parse_input1(input: &str) -> Result<Trail, ParseError> {
+    validate_grid(23, input, input, "#.^v<>")?;
     let grid = Grid::parse(input);
     let mut graph = Graph::new();
     // build graph
@@ -56,32 +254,17 @@ fn parse_input1(input: &str) -> Graph {
         }
     }
 
-    contract_graph(graph)
+    Trail::new(&grid, graph)
 }
 
-fn dfs(graph: &Graph, visited: &mut FxHashSet<Point>, pos: Point) -> Option<usize> {
-    if pos == END {
-        return Some(0);
-    }
-    let mut max = None;
-    for &(edge, next) in &graph[&pos] {
-        if !visited.contains(&next) {
-            visited.insert(next);
-            if let Some(dist) = dfs(graph, visited, next) {
-                max = Some(max.unwrap_or(0).max(edge + dist));
-            }
-            visited.remove(&next);
-        }
-    }
-    max
-}
 #[aoc(day23, part1)]
-fn part1(input: &Graph) -> usize {
-    dfs(input, &mut FxHashSet::default(), START).unwrap()
+fn part1(input: &Trail) -> Option<usize> {
+    input.longest_hike()
 }
 
 #[aoc_generator(day23, part2)]
-fn parse_input2(input: &str) -> Graph {
+fn parse_input2(input: &str) -> Result<Trail, ParseError> {
+    validate_grid(23, input, input, "#.^v<>")?;
     let grid = Grid::parse(input);
     let mut graph = Graph::new();
     // build graph
@@ -99,9 +282,95 @@ fn parse_input2(input: &str) -> Graph {
         }
     }
 
-    contract_graph(graph)
+    Trail::new(&grid, graph)
 }
 #[aoc(day23, part2)]
-fn part2(input: &Graph) -> usize {
-    dfs(input, &mut FxHashSet::default(), START).unwrap()
+fn part2(input: &Trail) -> Option<usize> {
+    input.longest_hike()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "#.#####################
+#.......#########...###
+#######.#########.#.###
+###.....#.>.>.###.#.###
+###v#####.#v#.###.#.###
+###.>...#.#.#.....#...#
+###v###.#.#.#########.#
+###...#.#.#.......#...#
+#####.#.#.#######.#.###
+#.....#.#.#.......#...#
+#.#####.#.#.#########v#
+#.#...#...#...###...>.#
+#.#.#v#######v###.###v#
+#...#.>.#...>.>.#.###.#
+#####v#.#.###v#.#.###.#
+#.....#...#...#.#.#...#
+#.#########.###.#.#.###
+#...###...#...#...#.###
+###.###.#.###v#####v###
+#...#...#.#.>.>.#.>.###
+#.###.###.#.###.#.#v###
+#.....###...###...#...#
+#####################.#";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(part1(&parse_input1(EXAMPLE).unwrap()), Some(94));
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(part2(&parse_input2(EXAMPLE).unwrap()), Some(154));
+    }
+
+    #[test]
+    fn rejects_multiple_openings() {
+        assert_eq!(
+            parse_input2("#..#\n#..#\n#.##").unwrap_err(),
+            ParseError::new(23, 1, 1, "expected exactly one opening, found 2")
+        );
+    }
+
+    #[test]
+    fn contracts_to_junctions() {
+        // seven junctions plus the entrance and exit, slopes and all
+        assert_eq!(parse_input1(EXAMPLE).unwrap().edges.len(), 9);
+        assert_eq!(parse_input2(EXAMPLE).unwrap().edges.len(), 9);
+    }
+
+    #[test]
+    fn search_records_hike() {
+        for (trail, expected) in [(parse_input1(EXAMPLE), 94), (parse_input2(EXAMPLE), 154)] {
+            let trail = trail.unwrap();
+            let (dist, hike) = trail.search().unwrap();
+            assert_eq!(dist, expected);
+            assert_eq!((hike[0], hike[hike.len() - 1]), (trail.start, trail.end));
+            assert!(hike.iter().all_unique());
+            let legs = hike.windows(2).map(|pair| {
+                let edges = &trail.edges[pair[0] as usize];
+                edges.iter().find(|&&(_, to)| to == pair[1]).unwrap().0
+            });
+            assert_eq!(legs.sum::<usize>(), dist);
+        }
+    }
+
+    #[test]
+    fn dot_highlights_longest_hike() {
+        let trail = parse_input1(EXAMPLE).unwrap();
+        let dot = trail.to_dot();
+        let edges = trail.edges.iter().map(Vec::len).sum::<usize>();
+        assert_eq!(dot.matches(" [label=").count(), 9 + edges);
+        let highlighted = dot
+            .lines()
+            .filter(|line| line.contains("->") && line.contains("red"));
+        let lengths = highlighted.map(|line| {
+            let (_, label) = line.split_once("label=\"").unwrap();
+            label.split('"').next().unwrap().parse::<usize>().unwrap()
+        });
+        assert_eq!(lengths.sum::<usize>(), 94);
+    }
 }
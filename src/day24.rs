@@ -0,0 +1,199 @@
+use crate::util::error::*;
+use crate::util::point::Point3;
+use itertools::Itertools;
+use num::{BigInt, BigRational, Signed, Zero};
+
+const TEST_AREA: (i64, i64) = (200_000_000_000_000, 400_000_000_000_000);
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Hailstone {
+    position: Point3<i64>,
+    velocity: Point3<i64>,
+}
+
+impl Hailstone {
+    // whether the XY paths of both hailstones cross inside the test area at some point in the future
+    fn crosses_within(&self, other: &Self, (min, max): (i64, i64)) -> bool {
+        let [p, v, o, w] = [self.position, self.velocity, other.position, other.velocity]
+            .map(|point| point.widen::<i128>().xy());
+        let mut det = v.cross(w);
+        // parallel paths never cross at a single point
+        if det == 0 {
+            return false;
+        }
+        let delta = o - p;
+        // times are t / det and s / det for self and other respectively
+        let mut t = delta.cross(w);
+        let mut s = delta.cross(v);
+        if det < 0 {
+            det = -det;
+            t = -t;
+            s = -s;
+        }
+        if t < 0 || s < 0 {
+            return false;
+        }
+        let (min, max) = (i128::from(min) * det, i128::from(max) * det);
+        let hit = p * det + v * t;
+        (min..=max).contains(&hit.x) && (min..=max).contains(&hit.y)
+    }
+}
+
+fn parse_triple(input: &str, triple: &str) -> Result<Point3<i64>, ParseError> {
+    let coords: [i64; 3] = triple
+        .split(',')
+        .map(|s| parse_token(24, input, s.trim()))
+        .collect::<Result<Vec<_>, _>>()?
+        .try_into()
+        .map_err(|_| ParseError::at(24, input, triple, "expected three coordinates"))?;
+    Ok(coords.into())
+}
+
+#[aoc_generator(day24)]
+fn parse_input(input: &str) -> Result<Vec<Hailstone>, ParseError> {
+    input
+        .lines()
+        .map(|line| {
+            let (position, velocity) = line.split_once('@').ok_or_else(|| {
+                ParseError::at(24, input, line, "expected `<position> @ <velocity>`")
+            })?;
+            Ok(Hailstone {
+                position: parse_triple(input, position)?,
+                velocity: parse_triple(input, velocity)?,
+            })
+        })
+        .collect()
+}
+
+fn count_crossings(hail: &[Hailstone], area: (i64, i64)) -> usize {
+    hail.iter()
+        .tuple_combinations()
+        .filter(|(a, b)| a.crosses_within(b, area))
+        .count()
+}
+
+#[aoc(day24, part1)]
+fn part1(input: &[Hailstone]) -> usize {
+    count_crossings(input, TEST_AREA)
+}
+
+fn big(point: Point3<i64>) -> Point3<BigInt> {
+    point.map(BigInt::from)
+}
+
+// rows of the matrix M such that M * p == p x w
+fn cross_matrix(w: Point3<BigInt>) -> [[BigInt; 3]; 3] {
+    let Point3 { x, y, z } = w;
+    let zero = BigInt::zero;
+    [
+        [zero(), z.clone(), -y.clone()],
+        [-z, zero(), x.clone()],
+        [y, -x, zero()],
+    ]
+}
+
+// Gauss-Jordan elimination over the rationals, None if the system is singular
+fn solve_linear(mut rows: Vec<Vec<BigRational>>) -> Option<Vec<BigRational>> {
+    let n = rows.len();
+    for col in 0..n {
+        let pivot = (col..n).find(|&r| !rows[r][col].is_zero())?;
+        rows.swap(col, pivot);
+        let lead = rows[col][col].clone();
+        for value in &mut rows[col] {
+            *value /= &lead;
+        }
+        for r in 0..n {
+            if r != col && !rows[r][col].is_zero() {
+                let factor = rows[r][col].clone();
+                let pivot_row = rows[col].clone();
+                for (value, p) in rows[r].iter_mut().zip(pivot_row) {
+                    *value -= &factor * p;
+                }
+            }
+        }
+    }
+    Some(rows.into_iter().map(|row| row[n].clone()).collect())
+}
+
+// A rock at P with velocity V hits hailstone i when (P - p_i) x (V - v_i) == 0. Subtracting
+// that equation for two hailstones cancels the P x V term and leaves three linear equations:
+// P x (v_j - v_i) + (p_j - p_i) x V == p_j x v_j - p_i x v_i
+fn throw_rock(hail: &[Hailstone]) -> Option<(Point3<BigInt>, Point3<BigInt>)> {
+    hail.iter().tuple_combinations().find_map(|(a, b, c)| {
+        let (pa, va) = (big(a.position), big(a.velocity));
+        let mut rows = Vec::with_capacity(6);
+        for other in [b, c] {
+            let (po, vo) = (big(other.position), big(other.velocity));
+            let p_coeffs = cross_matrix(vo.clone() - va.clone());
+            // (p_j - p_i) x V == -(V x (p_j - p_i))
+            let v_coeffs = cross_matrix(po.clone() - pa.clone()).map(|row| row.map(|x| -x));
+            let rhs: [BigInt; 3] = (po.cross(&vo) - pa.cross(&va)).into();
+            for i in 0..3 {
+                let row = p_coeffs[i]
+                    .iter()
+                    .chain(&v_coeffs[i])
+                    .chain([&rhs[i]])
+                    .map(|x| BigRational::from_integer(x.clone()))
+                    .collect();
+                rows.push(row);
+            }
+        }
+        let solution = solve_linear(rows)?;
+        if !solution.iter().all(|x| x.is_integer()) {
+            return None;
+        }
+        let mut values = solution.into_iter().map(|x| x.to_integer());
+        let mut next = || values.next().unwrap();
+        Some((
+            Point3::new(next(), next(), next()),
+            Point3::new(next(), next(), next()),
+        ))
+    })
+}
+
+#[aoc(day24, part2)]
+fn part2(input: &[Hailstone]) -> Result<BigInt, String> {
+    let (position, velocity) =
+        throw_rock(input).ok_or("no rock trajectory hits every hailstone")?;
+    // relative to each hailstone the rock must head straight at it: p == -t * v for some t >= 0
+    let origin = big(Point3::ORIGIN);
+    let hits_all = input.iter().all(|hail| {
+        let p = position.clone() - big(hail.position);
+        let v = velocity.clone() - big(hail.velocity);
+        // parallel and pointing opposite ways, unless the hailstone keeps pace with the rock
+        p.cross(&v) == origin && !p.dot(&v).is_positive() && (v != origin || p == origin)
+    });
+    if !hits_all {
+        return Err("rock trajectory misses some hailstones".to_string());
+    }
+    let Point3 { x, y, z } = position;
+    Ok(x + y + z)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "19, 13, 30 @ -2,  1, -2
+18, 19, 22 @ -1, -1, -2
+20, 25, 34 @ -2, -2, -4
+12, 31, 28 @ -1, -2, -1
+20, 19, 15 @  1, -5, -3";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(count_crossings(&parse_input(EXAMPLE).unwrap(), (7, 27)), 2);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(part2(&parse_input(EXAMPLE).unwrap()), Ok(BigInt::from(47)));
+    }
+
+    #[test]
+    fn throw_rock_example() {
+        let (position, velocity) = throw_rock(&parse_input(EXAMPLE).unwrap()).unwrap();
+        assert_eq!(position, big(Point3::new(24, 13, 10)));
+        assert_eq!(velocity, big(Point3::new(-3, 1, 2)));
+    }
+}
@@ -0,0 +1,124 @@
+use crate::util::error::*;
+use fxhash::FxHashMap;
+use std::collections::{BinaryHeap, HashMap};
+
+struct Wiring {
+    adj: Vec<FxHashMap<usize, u64>>,
+}
+
+#[aoc_generator(day25)]
+fn parse_input(input: &str) -> Result<Wiring, ParseError> {
+    let mut ids = HashMap::new();
+    let mut adj: Vec<FxHashMap<usize, u64>> = Vec::new();
+    let mut id = |name: &str, adj: &mut Vec<FxHashMap<usize, u64>>| {
+        *ids.entry(name.to_string()).or_insert_with(|| {
+            adj.push(FxHashMap::default());
+            adj.len() - 1
+        })
+    };
+    for line in input.lines() {
+        let (source, dests) = line.split_once(": ").ok_or_else(|| {
+            ParseError::at(25, input, line, "expected `<component>: <components>`")
+        })?;
+        let source = id(source, &mut adj);
+        for dest in dests.split_ascii_whitespace() {
+            let dest = id(dest, &mut adj);
+            if dest == source {
+                return Err(ParseError::at(
+                    25,
+                    input,
+                    line,
+                    "component is wired to itself",
+                ));
+            }
+            adj[source].insert(dest, 1);
+            adj[dest].insert(source, 1);
+        }
+    }
+    Ok(Wiring { adj })
+}
+
+// Stoer-Wagner global minimum cut, returns (cut weight, size of one side)
+fn min_cut(mut adj: Vec<FxHashMap<usize, u64>>) -> (u64, usize) {
+    let n = adj.len();
+    let mut size = vec![1; n];
+    let mut active: Vec<usize> = (0..n).collect();
+    let mut best = (u64::MAX, 0);
+
+    while active.len() > 1 {
+        // maximum adjacency ordering; the last vertex added is the most tightly connected
+        let mut weights = vec![0; n];
+        let mut added = vec![false; n];
+        let mut heap = BinaryHeap::new();
+        let (mut prev, mut last, mut cut) = (usize::MAX, usize::MAX, 0);
+        for _ in 0..active.len() {
+            let (weight, node) = loop {
+                match heap.pop() {
+                    Some((w, v)) if added[v] || w != weights[v] => continue,
+                    Some(next) => break next,
+                    // disconnected graph, carry on from any vertex not yet added
+                    None => break (0, *active.iter().find(|&&v| !added[v]).unwrap()),
+                }
+            };
+            added[node] = true;
+            (prev, last, cut) = (last, node, weight);
+            for (&next, &w) in &adj[node] {
+                if !added[next] {
+                    weights[next] += w;
+                    heap.push((weights[next], next));
+                }
+            }
+        }
+        if cut < best.0 {
+            best = (cut, size[last]);
+        }
+
+        // merge the last vertex into the one added before it
+        let edges = std::mem::take(&mut adj[last]);
+        for (next, w) in edges {
+            adj[next].remove(&last);
+            if next != prev {
+                *adj[prev].entry(next).or_default() += w;
+                *adj[next].entry(prev).or_default() += w;
+            }
+        }
+        size[prev] += size[last];
+        active.retain(|&v| v != last);
+    }
+    best
+}
+
+#[aoc(day25, part1)]
+fn part1(input: &Wiring) -> usize {
+    let (_, side) = min_cut(input.adj.clone());
+    side * (input.adj.len() - side)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "jqt: rhn xhk nvd
+rsh: frs pzl lsr
+xhk: hfx
+cmg: qnr nvd lhk bvb
+rhn: xhk bvb hfx
+bvb: xhk hfx
+pzl: lsr hfx nvd
+qnr: nvd
+ntq: jqt hfx bvb xhk
+nvd: lhk
+lsr: lhk
+rzs: qnr cmg lsr rsh
+frs: qnr lhk lsr";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(part1(&parse_input(EXAMPLE).unwrap()), 54);
+    }
+
+    #[test]
+    fn min_cut_example() {
+        assert_eq!(min_cut(parse_input(EXAMPLE).unwrap().adj).0, 3);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::util::error::*;
 use itertools::Itertools;
 use regex::Regex;
 use std::collections::HashSet;
@@ -7,12 +8,13 @@ type Position = (usize, usize);
 type Input = (Vec<(String, Position)>, HashSet<Position>);
 
 #[aoc_generator(day3, part1)]
-fn parse_input1(input: &str) -> Input {
+fn parse_input1(input: &str) -> Result<Input, ParseError> {
     let mut numbers = vec![];
     let num_re = Regex::new(r"\d+").unwrap();
 
     for (row, line) in input.lines().enumerate() {
         for num in num_re.find_iter(line) {
+            parse_token::<u32>(3, input, num.as_str())?;
             numbers.push((num.as_str().to_owned(), (row, num.start())));
         }
     }
@@ -27,7 +29,7 @@ fn parse_input1(input: &str) -> Input {
         })
         .collect::<HashSet<_, _>>();
 
-    (numbers, symbols)
+    Ok((numbers, symbols))
 }
 
 fn symbol_adjacent((num_str, (row, col)): (&str, Position), symbols: &HashSet<Position>) -> bool {
@@ -56,12 +58,13 @@ fn part1((nums, symbols): &Input) -> u32 {
 }
 
 #[aoc_generator(day3, part2)]
-fn parse_input2(input: &str) -> Input {
+fn parse_input2(input: &str) -> Result<Input, ParseError> {
     let mut numbers = vec![];
     let num_re = Regex::new(r"\d+").unwrap();
 
     for (row, line) in input.lines().enumerate() {
         for num in num_re.find_iter(line) {
+            parse_token::<u32>(3, input, num.as_str())?;
             numbers.push((num.as_str().to_owned(), (row, num.start())));
         }
     }
@@ -76,7 +79,7 @@ fn parse_input2(input: &str) -> Input {
         })
         .collect::<HashSet<_, _>>();
 
-    (numbers, symbols)
+    Ok((numbers, symbols))
 }
 
 #[aoc(day3, part2)]
@@ -112,3 +115,29 @@ fn part2((nums, gears): &Input) -> u32 {
 
     ratios.into_iter().sum()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(part1(&parse_input1(EXAMPLE).unwrap()), 4361);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(part2(&parse_input2(EXAMPLE).unwrap()), 467_835);
+    }
+}
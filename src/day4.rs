@@ -1,3 +1,4 @@
+use crate::util::error::*;
 use itertools::Itertools;
 use std::collections::{HashMap, HashSet};
 
@@ -6,31 +7,34 @@ struct Card {
     matches: u32,
 }
 
+fn parse_nums(input: &str, nums: &str) -> Result<HashSet<u32>, ParseError> {
+    nums.split_ascii_whitespace()
+        .map(|num| parse_token(4, input, num))
+        .collect()
+}
+
 #[aoc_generator(day4)]
-fn parse_input(input: &str) -> Vec<Card> {
+fn parse_input(input: &str) -> Result<Vec<Card>, ParseError> {
     input
         .lines()
         .map(|line| {
-            let line = line.strip_prefix("Card").unwrap().trim();
-            let (id, nums) = line.split_once(':').unwrap();
+            let (id, nums) = line
+                .strip_prefix("Card")
+                .and_then(|line| line.split_once(':'))
+                .ok_or_else(|| ParseError::at(4, input, line, "expected `Card <id>:`"))?;
 
-            let (winning, rest) = nums.split_once('|').unwrap();
+            let (winning, rest) = nums
+                .split_once('|')
+                .ok_or_else(|| ParseError::at(4, input, nums, "expected `|`"))?;
 
-            let winning: HashSet<u32> = winning
-                .split_ascii_whitespace()
-                .map(|num| num.parse::<u32>().unwrap())
-                .collect::<HashSet<_>>();
-
-            let rest = rest
-                .split_ascii_whitespace()
-                .map(|num| num.parse::<u32>().unwrap())
-                .collect::<HashSet<_>>();
+            let winning = parse_nums(input, winning)?;
+            let rest = parse_nums(input, rest)?;
 
             let matches = winning.intersection(&rest);
-            Card {
-                id: id.parse().unwrap(),
+            Ok(Card {
+                id: parse_token(4, input, id.trim())?,
                 matches: matches.count() as u32,
-            }
+            })
         })
         .collect()
 }
@@ -69,3 +73,25 @@ fn part2(input: &[Card]) -> u32 {
 
     card_counts.values().sum()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(part1(&parse_input(EXAMPLE).unwrap()), 13);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(part2(&parse_input(EXAMPLE).unwrap()), 30);
+    }
+}
@@ -1,147 +1,478 @@
+use crate::util::error::*;
+use crate::util::interval::*;
 use itertools::Itertools;
-use std::collections::BTreeSet;
 
+/// One line of a map: `len` values from `source_start` on go to `dest_start` on.
 #[derive(Debug, PartialEq)]
-struct MapEntry {
-    dest_start: u64,
-    source_start: u64,
-    len: u64,
+pub struct MapEntry {
+    pub dest_start: u64,
+    pub source_start: u64,
+    pub len: u64,
 }
 
 impl MapEntry {
-    fn convert(&self, value: u64) -> Option<u64> {
-        if self.source_start <= value && value < self.source_start + self.len {
-            Some(value - self.source_start + self.dest_start)
-        } else {
-            None
-        }
+    fn source(&self) -> std::ops::Range<u64> {
+        self.source_start..self.source_start + self.len
     }
 }
 
 #[derive(Debug, PartialEq)]
-struct Map(Vec<MapEntry>);
+struct Map {
+    source: String,
+    dest: String,
+    function: PiecewiseAffine,
+}
 
-type Range = (u64, u64);
 impl Map {
-    fn convert(&self, value: u64) -> u64 {
-        if let Some(dest) = self.0.iter().find_map(|entry| entry.convert(value)) {
-            dest
-        } else {
-            value
-        }
+    /// Sorted entries covering every value below `u64::MAX`, identity stretches included.
+    fn entries(&self) -> Vec<MapEntry> {
+        self.function
+            .segments()
+            .into_iter()
+            .map(|(range, _)| MapEntry {
+                dest_start: self.function.apply(range.start),
+                source_start: range.start,
+                len: range.end - range.start,
+            })
+            .collect()
     }
 
-    fn convert_range(&self, (range_start, range_len): Range) -> Vec<Range> {
-        let range_end = range_start + range_len;
-        let mut slices = self.0.iter().fold(BTreeSet::new(), |mut set, entry| {
-            let source_start = entry.source_start;
-            let len = entry.len;
-            let source_end = source_start + len;
-
-            if range_end < source_start || range_start >= source_end {
-                set
-            } else {
-                if source_start > range_start {
-                    set.insert(source_start);
-                }
-
-                if source_end < range_end {
-                    set.insert(source_end);
-                }
-                set
-            }
-        });
-        slices.insert(range_end);
-        slices
-            .iter()
-            .fold(
-                (Vec::new(), range_start),
-                |(mut ranges, current): (Vec<Range>, u64), pos| {
-                    ranges.push((self.convert(current), pos - current));
-                    (ranges, *pos)
-                },
-            )
-            .0
+    /// Maps every destination back to its source, if no two sources share a destination.
+    fn inverse(&self) -> Option<Map> {
+        Some(Map {
+            source: self.dest.clone(),
+            dest: self.source.clone(),
+            function: self.function.inverse()?,
+        })
     }
 }
+
 #[derive(Debug, PartialEq)]
 struct Almanac {
     seeds: Vec<u64>,
+    // each map picks up where the one before it left off, from seed to location
     maps: Vec<Map>,
 }
 
 impl Almanac {
-    fn seed_to_location(&self, seed: u64) -> u64 {
-        self.maps.iter().fold(seed, |val, map| map.convert(val))
+    /// Every category in the order the maps convert between them.
+    fn categories(&self) -> impl Iterator<Item = &str> {
+        std::iter::once("seed").chain(self.maps.iter().map(|map| map.dest.as_str()))
     }
 
-    fn seed_ranges(&self) -> Vec<Range> {
-        self.seeds.iter().copied().tuples::<(_, _)>().collect()
+    /// A single map between any two categories, running backwards through the maps if
+    /// `from` comes after `to`. None if either category is unknown or going backwards
+    /// isn't possible because some maps send two values to the same place.
+    fn converter(&self, from: &str, to: &str) -> Option<Map> {
+        let position = |name| self.categories().position(|category| category == name);
+        let (start, end) = (position(from)?, position(to)?);
+        let (first, last) = if start <= end { (from, to) } else { (to, from) };
+        let forward = Map {
+            source: first.to_string(),
+            dest: last.to_string(),
+            function: self.maps[start.min(end)..start.max(end)]
+                .iter()
+                .fold(PiecewiseAffine::identity(), |acc, map| {
+                    acc.then(&map.function)
+                }),
+        };
+        if start <= end {
+            Some(forward)
+        } else {
+            forward.inverse()
+        }
+    }
+
+    /// Every seed ending up at `location`.
+    fn seeds_at(&self, location: u64) -> Vec<u64> {
+        self.maps.iter().rev().fold(vec![location], |values, map| {
+            values
+                .into_iter()
+                .flat_map(|value| map.function.preimages(value))
+                .collect()
+        })
+    }
+
+    fn seed_ranges(&self) -> IntervalSet<u64> {
+        self.seeds
+            .iter()
+            .tuples()
+            .map(|(&start, &len)| start..start + len)
+            .collect()
     }
 }
 
-fn parse_seeds(input: &str) -> Vec<u64> {
-    input
+fn parse_seeds(input: &str, block: &str) -> Result<Vec<u64>, ParseError> {
+    let err = |token, message| ParseError::at(5, input, token, message);
+    let tokens = block
         .strip_prefix("seeds: ")
-        .unwrap()
+        .ok_or_else(|| err(block, "expected `seeds: `"))?
         .split_ascii_whitespace()
-        .filter_map(|s| s.parse().ok())
-        .collect()
+        .collect::<Vec<_>>();
+    let seeds = tokens
+        .iter()
+        .map(|s| parse_token(5, input, s))
+        .collect::<Result<Vec<u64>, _>>()?;
+    // part 2 reads the seeds as pairs of range start and length
+    if tokens.len() % 2 != 0 {
+        let last = tokens[tokens.len() - 1];
+        return Err(err(last, "expected a length after the last seed"));
+    }
+    for (pair, start) in seeds.chunks(2).zip(tokens.iter().step_by(2)) {
+        if pair[0].checked_add(pair[1]).is_none() {
+            return Err(err(start, "range runs past the largest value"));
+        }
+    }
+    Ok(seeds)
 }
 
-fn parse_map(input: &str) -> Map {
-    let entries = input
-        .lines()
-        .skip(1)
+fn parse_map(input: &str, block: &str) -> Result<Map, ParseError> {
+    let mut lines = block.lines();
+    let header = lines.next().unwrap_or(block);
+    let (source, dest) = header
+        .strip_suffix(" map:")
+        .and_then(|names| names.split_once("-to-"))
+        .ok_or_else(|| {
+            ParseError::at(
+                5,
+                input,
+                header,
+                "expected `<source>-to-<destination> map:`",
+            )
+        })?;
+
+    let entries = lines
         .map(|line| {
-            let (dest_start, source_start, len) = line
+            let (dest_start, source_start, len): (u64, u64, u64) = line
                 .split_ascii_whitespace()
-                .filter_map(|s| s.parse().ok())
+                .map(|s| parse_token(5, input, s))
+                .collect::<Result<Vec<_>, _>>()?
+                .into_iter()
                 .collect_tuple()
-                .unwrap();
+                .ok_or_else(|| ParseError::at(5, input, line, "expected three numbers"))?;
+
+            let err = |message| ParseError::at(5, input, line, message);
+            if source_start.max(dest_start).checked_add(len).is_none() {
+                return Err(err("range runs past the largest value"));
+            }
 
-            MapEntry {
+            let entry = MapEntry {
                 dest_start,
                 source_start,
                 len,
-            }
+            };
+            Ok((line, entry))
         })
-        .collect();
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for (i, (line, entry)) in entries.iter().enumerate() {
+        let range = entry.source();
+        let overlaps = entries[..i].iter().any(|(_, other)| {
+            let other = other.source();
+            other.start < range.end && range.start < other.end
+        });
+        if overlaps {
+            return Err(ParseError::at(5, input, line, "overlapping source range"));
+        }
+    }
 
-    Map(entries)
+    Ok(Map {
+        source: source.to_string(),
+        dest: dest.to_string(),
+        function: PiecewiseAffine::from_first_match(entries.iter().map(|(_, entry)| {
+            let offset = i128::from(entry.dest_start) - i128::from(entry.source_start);
+            (entry.source(), offset)
+        })),
+    })
 }
 
 #[aoc_generator(day5)]
-fn parse_input(input: &str) -> Almanac {
+fn parse_input(input: &str) -> Result<Almanac, ParseError> {
     let mut blocks = input.split("\n\n");
-    let seeds = parse_seeds(blocks.next().unwrap());
+    let seeds = parse_seeds(input, blocks.next().unwrap_or_default())?;
 
-    let maps = blocks.map(parse_map).collect();
+    let mut maps: Vec<Map> = Vec::new();
+    let mut category = "seed".to_string();
+    for block in blocks {
+        let map = parse_map(input, block)?;
+        if map.source != category {
+            let message = format!("expected a map from `{category}`");
+            return Err(ParseError::at(5, input, block, message));
+        }
+        // categories are looked up by name, so each may only turn up once
+        if map.dest == "seed" || maps.iter().any(|other| other.dest == map.dest) {
+            let message = format!("category `{}` appears twice", map.dest);
+            return Err(ParseError::at(5, input, block, message));
+        }
+        category.clone_from(&map.dest);
+        maps.push(map);
+    }
+    if category != "location" {
+        let message = format!("expected the maps to end at `location`, not `{category}`");
+        return Err(ParseError::new(5, input.lines().count(), 1, message));
+    }
+
+    Ok(Almanac { seeds, maps })
+}
+
+/// Every seed in the input's almanac ending up at `location`.
+pub fn seeds_at(input: &str, location: u64) -> Result<Vec<u64>, ParseError> {
+    Ok(parse_input(input)?.seeds_at(location))
+}
 
-    Almanac { seeds, maps }
+/// The input's almanac as a single map between two categories, as sorted entries.
+/// None if the conversion doesn't exist, see `Almanac::converter`.
+pub fn map_entries(input: &str, from: &str, to: &str) -> Result<Option<Vec<MapEntry>>, ParseError> {
+    let almanac = parse_input(input)?;
+    Ok(almanac.converter(from, to).map(|map| map.entries()))
 }
 
 #[aoc(day5, part1)]
-fn part1(input: &Almanac) -> u64 {
+fn part1(input: &Almanac) -> Option<u64> {
+    let location = input.converter("seed", "location")?;
     input
         .seeds
         .iter()
-        .map(|&seed| input.seed_to_location(seed))
+        .map(|&seed| location.function.apply(seed))
         .min()
-        .unwrap()
 }
 
 #[aoc(day5, part2)]
-fn part2(input: &Almanac) -> u64 {
-    let mut ranges = input.seed_ranges();
-    let mut next = Vec::new();
+fn part2(input: &Almanac) -> Option<u64> {
+    let location = input.converter("seed", "location")?;
+    location.function.min_over(&input.seed_ranges())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    for map in &input.maps {
-        for range in ranges {
-            next.extend(map.convert_range(range));
+    const EXAMPLE: &str = "seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+temperature-to-humidity map:
+0 69 1
+1 0 69
+
+humidity-to-location map:
+60 56 37
+56 93 4";
+
+    const MAP: &str = "seed-to-soil map:
+50 98 2
+52 50 48";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(part1(&parse_input(EXAMPLE).unwrap()), Some(35));
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(part2(&parse_input(EXAMPLE).unwrap()), Some(46));
+    }
+
+    #[test]
+    fn convert_range_inside_entry() {
+        let map = parse_map(MAP, MAP).unwrap();
+        assert_eq!(map.function.apply_set(&(79..93).into()), (81..95).into());
+    }
+
+    #[test]
+    fn convert_range_across_entries() {
+        let map = parse_map(MAP, MAP).unwrap();
+        assert_eq!(
+            map.function.apply_set(&(90..110).into()).ranges(),
+            [50..52, 92..110]
+        );
+    }
+
+    #[test]
+    fn convert_range_outside_entries() {
+        let map = parse_map(MAP, MAP).unwrap();
+        assert_eq!(map.function.apply_set(&(10..15).into()), (10..15).into());
+    }
+
+    #[test]
+    fn composed_matches_each_map() {
+        let almanac = parse_input(EXAMPLE).unwrap();
+        let location = almanac.converter("seed", "location").unwrap();
+        for seed in 0..120 {
+            let expected = almanac
+                .maps
+                .iter()
+                .fold(seed, |value, map| map.function.apply(value));
+            assert_eq!(location.function.apply(seed), expected);
+        }
+    }
+
+    #[test]
+    fn composed_entries_cover_everything() {
+        let almanac = parse_input(EXAMPLE).unwrap();
+        let entries = almanac.converter("seed", "location").unwrap().entries();
+        assert_eq!(entries[0].source_start, 0);
+        assert!(entries
+            .windows(2)
+            .all(|pair| pair[0].source_start + pair[0].len == pair[1].source_start));
+        let last = entries.last().unwrap();
+        assert_eq!(last.source_start + last.len, u64::MAX);
+    }
+
+    #[test]
+    fn reverse_lookup() {
+        let almanac = parse_input(EXAMPLE).unwrap();
+        assert_eq!(almanac.seeds_at(46), [82]);
+        assert_eq!(almanac.seeds_at(35), [13]);
+        let location = almanac.converter("seed", "location").unwrap();
+        let seeds = almanac.converter("location", "seed").unwrap();
+        assert_eq!(
+            (seeds.source.as_str(), seeds.dest.as_str()),
+            ("location", "seed")
+        );
+        for seed in [79, 14, 55, 13] {
+            assert_eq!(seeds.function.apply(location.function.apply(seed)), seed);
         }
-        ranges = next;
-        next = Vec::new();
     }
-    ranges.iter().map(|range| range.0).min().unwrap()
+
+    #[test]
+    fn public_queries() {
+        assert_eq!(seeds_at(EXAMPLE, 46), Ok(vec![82]));
+        let entries = map_entries(EXAMPLE, "seed", "soil").unwrap().unwrap();
+        let entries = entries
+            .iter()
+            .map(|entry| (entry.dest_start, entry.source_start, entry.len))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            entries,
+            [
+                (0, 0, 50),
+                (52, 50, 48),
+                (50, 98, 2),
+                (100, 100, u64::MAX - 100)
+            ]
+        );
+        assert_eq!(map_entries(EXAMPLE, "seed", "gold"), Ok(None));
+    }
+
+    #[test]
+    fn reports_short_map_line() {
+        let input = "seeds: 1 2\n\nseed-to-soil map:\n50 98 2\n52 50";
+        assert_eq!(
+            parse_input(input).unwrap_err(),
+            ParseError::new(5, 5, 1, "expected three numbers")
+        );
+    }
+
+    #[test]
+    fn converts_between_any_categories() {
+        let almanac = parse_input(EXAMPLE).unwrap();
+        // seed 79 has soil 81, fertilizer 81, water 81, light 74, temperature 78 and humidity 78
+        let soil_to_humidity = almanac.converter("soil", "humidity").unwrap();
+        assert_eq!(soil_to_humidity.function.apply(81), 78);
+        let light_to_water = almanac.converter("light", "water").unwrap();
+        assert_eq!(light_to_water.function.apply(74), 81);
+        assert!(almanac.converter("seed", "seed").is_some());
+        assert!(almanac.converter("seed", "gold").is_none());
+    }
+
+    #[test]
+    fn reports_broken_chain() {
+        let input = "seeds: 1 2\n\nseed-to-soil map:\n1 2 3\n\nwater-to-light map:\n1 2 3";
+        assert_eq!(
+            parse_input(input).unwrap_err(),
+            ParseError::new(5, 6, 1, "expected a map from `soil`")
+        );
+        let input = "seeds: 1 2\n\nseed-to-soil map:\n1 2 3";
+        assert_eq!(
+            parse_input(input).unwrap_err(),
+            ParseError::new(
+                5,
+                4,
+                1,
+                "expected the maps to end at `location`, not `soil`"
+            )
+        );
+        let input = "seeds: 1 2\n\nseed to soil:\n1 2 3";
+        assert_eq!(parse_input(input).unwrap_err().line, 3);
+    }
+
+    #[test]
+    fn reports_repeated_category() {
+        let input = "seeds: 1 2\n\nseed-to-soil map:\n1 2 3\n\nsoil-to-water map:\n1 2 3\n\n\
+                     water-to-soil map:\n1 2 3\n\nsoil-to-location map:\n1 2 3";
+        assert_eq!(
+            parse_input(input).unwrap_err(),
+            ParseError::new(5, 9, 1, "category `soil` appears twice")
+        );
+        let input = "seeds: 1 2\n\nseed-to-seed map:\n1 2 3";
+        assert_eq!(
+            parse_input(input).unwrap_err(),
+            ParseError::new(5, 3, 1, "category `seed` appears twice")
+        );
+    }
+
+    #[test]
+    fn reports_out_of_range_entries() {
+        let input = format!("seed-to-soil map:\n0 {} 2", u64::MAX - 1);
+        assert_eq!(
+            parse_map(&input, &input).unwrap_err(),
+            ParseError::new(5, 2, 1, "range runs past the largest value")
+        );
+        let input = format!("seeds: 1 2 {} 2", u64::MAX - 1);
+        assert_eq!(
+            parse_seeds(&input, &input).unwrap_err(),
+            ParseError::new(5, 1, 12, "range runs past the largest value")
+        );
+        let input = "seeds: 1 2 3";
+        assert_eq!(
+            parse_seeds(input, input).unwrap_err(),
+            ParseError::new(5, 1, 12, "expected a length after the last seed")
+        );
+    }
+
+    #[test]
+    fn distant_destinations() {
+        let input = format!(
+            "seeds: 0 1\n\nseed-to-soil map:\n{} 0 1\n\nsoil-to-location map:\n{} {} 1",
+            i64::MAX,
+            u64::MAX - 1,
+            i64::MAX
+        );
+        let almanac = parse_input(&input).unwrap();
+        let location = almanac.converter("seed", "location").unwrap();
+        assert_eq!(location.function.apply(0), u64::MAX - 1);
+        assert_eq!(part2(&almanac), Some(u64::MAX - 1));
+    }
+
+    #[test]
+    fn reports_overlapping_sources() {
+        let input = "seeds: 1 2\n\nseed-to-location map:\n50 98 2\n0 90 9";
+        assert_eq!(
+            parse_input(input).unwrap_err(),
+            ParseError::new(5, 5, 1, "overlapping source range")
+        );
+    }
 }
@@ -1,46 +1,129 @@
-#[derive(Debug, PartialOrd, PartialEq)]
-struct Race {
-    time: u64,
-    record: u64,
-}
-
-impl Race {
-    fn num_wins(&self) -> u64 {
-        (0..self.time)
-            .map(|time| time * (self.time - time))
-            .filter(|&dist| dist > self.record)
-            .count() as u64
-    }
-}
-static RACES: [Race; 4] = [
-    Race {
-        time: 48,
-        record: 390,
-    },
-    Race {
-        time: 98,
-        record: 1103,
-    },
-    Race {
-        time: 90,
-        record: 1112,
-    },
-    Race {
-        time: 83,
-        record: 1360,
-    },
-];
-
-#[aoc(day6, part1)]
-fn part1(_: &str) -> u64 {
-    RACES.iter().map(Race::num_wins).product()
-}
-
-#[aoc(day6, part2)]
-fn part2(_: &str) -> u64 {
-    Race {
-        time: 48_989_083,
-        record: 390_110_311_121_360,
-    }
-    .num_wins()
-}
+use crate::util::error::*;
+
+#[derive(Debug, PartialOrd, PartialEq)]
+struct Race {
+    time: u64,
+    record: u64,
+}
+
+impl Race {
+    fn distance(&self, hold: u64) -> u128 {
+        u128::from(hold) * u128::from(self.time - hold)
+    }
+
+    fn beats_record(&self, hold: u64) -> bool {
+        self.distance(hold) > u128::from(self.record)
+    }
+
+    fn num_wins(&self) -> u64 {
+        let time = u128::from(self.time);
+        let record = u128::from(self.record);
+        // hold * (time - hold) > record has roots at (time +- sqrt(time^2 - 4 * record)) / 2
+        let Some(disc) = (time * time).checked_sub(4 * record) else {
+            return 0;
+        };
+        // isqrt is exact, so the estimate is off by at most one in either direction
+        let mut low = ((time - disc.isqrt()) / 2) as u64;
+        while low > 0 && self.beats_record(low - 1) {
+            low -= 1;
+        }
+        while low <= self.time / 2 && !self.beats_record(low) {
+            low += 1;
+        }
+        if low > self.time / 2 {
+            return 0;
+        }
+        // the distance curve is symmetric around time / 2
+        self.time - 2 * low + 1
+    }
+}
+
+#[derive(Debug)]
+struct Input {
+    races: Vec<Race>,
+    kerned: Race,
+}
+
+// returns the separate numbers and the single number formed by ignoring the spaces
+fn parse_line(input: &str, line_no: usize, prefix: &str) -> Result<(Vec<u64>, u64), ParseError> {
+    let line = input.lines().nth(line_no).unwrap_or_default();
+    let nums = line
+        .strip_prefix(prefix)
+        .ok_or_else(|| ParseError::new(6, line_no + 1, 1, format!("expected `{prefix}`")))?;
+    let values = nums
+        .split_ascii_whitespace()
+        .map(|s| parse_token(6, input, s))
+        .collect::<Result<_, _>>()?;
+    let kerned = nums
+        .split_ascii_whitespace()
+        .collect::<String>()
+        .parse()
+        .map_err(|_| ParseError::at(6, input, nums, "kerned number is out of range"))?;
+    Ok((values, kerned))
+}
+
+#[aoc_generator(day6)]
+fn parse_input(input: &str) -> Result<Input, ParseError> {
+    let (times, time) = parse_line(input, 0, "Time:")?;
+    let (records, record) = parse_line(input, 1, "Distance:")?;
+    if times.len() != records.len() {
+        return Err(ParseError::new(
+            6,
+            2,
+            1,
+            format!(
+                "expected {} distances, found {}",
+                times.len(),
+                records.len()
+            ),
+        ));
+    }
+
+    let races = times
+        .into_iter()
+        .zip(records)
+        .map(|(time, record)| Race { time, record })
+        .collect();
+    let kerned = Race { time, record };
+
+    Ok(Input { races, kerned })
+}
+
+#[aoc(day6, part1)]
+fn part1(input: &Input) -> u64 {
+    input.races.iter().map(Race::num_wins).product()
+}
+
+#[aoc(day6, part2)]
+fn part2(input: &Input) -> u64 {
+    input.kerned.num_wins()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "Time:      7  15   30
+Distance:  9  40  200";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(part1(&parse_input(EXAMPLE).unwrap()), 288);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(part2(&parse_input(EXAMPLE).unwrap()), 71503);
+    }
+
+    #[test]
+    fn num_wins_matches_brute_force() {
+        for time in 0..50 {
+            for record in 0..700 {
+                let race = Race { time, record };
+                let expected = (0..=time).filter(|&hold| race.beats_record(hold)).count();
+                assert_eq!(race.num_wins(), expected as u64, "{race:?}");
+            }
+        }
+    }
+}
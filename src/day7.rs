@@ -1,15 +1,34 @@
+use crate::util::error::*;
+
 #[derive(Copy, Clone)]
 struct Hand {
     bid: u32,
     strength: u32,
 }
 
+fn split_hand(line: &str) -> Result<(Vec<(u32, char)>, u32), ParseError> {
+    let (cards, bid) = line
+        .split_once(' ')
+        .ok_or_else(|| ParseError::at(7, line, line, "expected `<cards> <bid>`"))?;
+    if cards.chars().count() != 5 {
+        return Err(ParseError::at(7, line, cards, "expected five cards"));
+    }
+    let cards = cards
+        .char_indices()
+        .map(|(i, card)| match card {
+            'A' | 'K' | 'Q' | 'J' | 'T' | '2'..='9' => Ok((i as u32, card)),
+            _ => Err(ParseError::at(7, line, &cards[i..], "unknown card")),
+        })
+        .collect::<Result<_, _>>()?;
+    Ok((cards, parse_token(7, line, bid)?))
+}
+
 impl Hand {
-    fn new(line: &str) -> Self {
+    fn new(line: &str) -> Result<Self, ParseError> {
         let mut strength = 0;
         let mut counts = [0; 13];
-        let (cards, bid) = line.split_once(' ').unwrap();
-        for (i, card) in cards.char_indices() {
+        let (cards, bid) = split_hand(line)?;
+        for (i, card) in cards {
             let value = match card {
                 'A' => 12,
                 'K' => 11,
@@ -23,17 +42,14 @@ impl Hand {
         }
         counts.sort_unstable();
         strength |= hand_type(counts[12], counts[11]) << 20;
-        Hand {
-            strength,
-            bid: bid.parse().unwrap(),
-        }
+        Ok(Hand { strength, bid })
     }
-    fn new_with_jokers(line: &str) -> Self {
+    fn new_with_jokers(line: &str) -> Result<Self, ParseError> {
         let mut strength = 0;
         let mut jokers = 0;
         let mut counts = [0; 13];
-        let (cards, bid) = line.split_once(' ').unwrap();
-        for (i, card) in cards.char_indices() {
+        let (cards, bid) = split_hand(line)?;
+        for (i, card) in cards {
             let value = match card {
                 'A' => 12,
                 'K' => 11,
@@ -52,10 +68,7 @@ impl Hand {
         }
         counts.sort_unstable();
         strength |= hand_type(counts[12] + jokers, counts[11]) << 20;
-        Hand {
-            strength,
-            bid: bid.parse().unwrap(),
-        }
+        Ok(Hand { strength, bid })
     }
 }
 
@@ -78,14 +91,25 @@ fn hand_type(max_count: u32, second_max_count: u32) -> u32 {
     }
 }
 
+fn parse_hands(
+    input: &str,
+    parse: fn(&str) -> Result<Hand, ParseError>,
+) -> Result<Vec<Hand>, ParseError> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| parse(line).map_err(|e| e.offset_lines(i)))
+        .collect()
+}
+
 #[aoc_generator(day7, part1)]
-fn parse_input1(input: &str) -> Vec<Hand> {
-    input.lines().map(Hand::new).collect()
+fn parse_input1(input: &str) -> Result<Vec<Hand>, ParseError> {
+    parse_hands(input, Hand::new)
 }
 
 #[aoc_generator(day7, part2)]
-fn parse_input2(input: &str) -> Vec<Hand> {
-    input.lines().map(Hand::new_with_jokers).collect()
+fn parse_input2(input: &str) -> Result<Vec<Hand>, ParseError> {
+    parse_hands(input, Hand::new_with_jokers)
 }
 
 #[aoc(day7, part1)]
@@ -111,3 +135,35 @@ fn part2(input: &[Hand]) -> u32 {
         .try_into()
         .unwrap()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "32T3K 765
+T55J5 684
+KK677 28
+KTJJT 220
+QQQJA 483";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(part1(&parse_input1(EXAMPLE).unwrap()), 6440);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(part2(&parse_input2(EXAMPLE).unwrap()), 5905);
+    }
+
+    #[test]
+    fn hand_types() {
+        assert_eq!(hand_type(5, 0), 6);
+        assert_eq!(hand_type(4, 1), 5);
+        assert_eq!(hand_type(3, 2), 4);
+        assert_eq!(hand_type(3, 1), 3);
+        assert_eq!(hand_type(2, 2), 2);
+        assert_eq!(hand_type(2, 1), 1);
+        assert_eq!(hand_type(1, 1), 0);
+    }
+}
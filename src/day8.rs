@@ -1,5 +1,6 @@
+use crate::util::dot::*;
+use crate::util::error::*;
 use gcd::binary_u64;
-use itertools::Itertools;
 use regex::Regex;
 use std::collections::HashMap;
 #[derive(Debug, Copy, Clone)]
@@ -8,16 +9,6 @@ enum Instruction {
     Right,
 }
 
-impl Instruction {
-    fn from_char(c: char) -> Self {
-        match c {
-            'L' => Self::Left,
-            'R' => Self::Right,
-            _ => unreachable!(),
-        }
-    }
-}
-
 type NodeMap = HashMap<String, (String, String)>;
 #[derive(Clone, Debug)]
 struct Input {
@@ -25,30 +16,116 @@ struct Input {
     nodes: NodeMap,
 }
 
-#[aoc_generator(day8)]
-fn parse_input(input: &str) -> Input {
-    let re = Regex::new(r"\w{3}").unwrap();
+impl Input {
+    /// The node map in Graphviz DOT format with the start and end nodes of part 2 highlighted.
+    fn to_dot(&self) -> String {
+        let mut dot = Dot::new();
+        let mut names = self.nodes.keys().collect::<Vec<_>>();
+        names.sort();
+        for name in names {
+            let highlight = name.ends_with('A') || name.ends_with('Z');
+            dot.node(name, name, highlight);
+            let (left, right) = &self.nodes[name];
+            if left == right {
+                dot.edge(name, left, "LR", false);
+            } else {
+                dot.edge(name, left, "L", false);
+                dot.edge(name, right, "R", false);
+            }
+        }
+        dot.finish()
+    }
+}
+
+/// The node map of the input in Graphviz DOT format.
+pub fn to_dot(input: &str) -> Result<String, ParseError> {
+    Ok(parse_network(input)?.to_dot())
+}
 
-    let (instructions, rest) = input.split_once("\n\n").unwrap();
+fn parse_network(input: &str) -> Result<Input, ParseError> {
+    let re = Regex::new(r"^(\w{3}) = \((\w{3}), (\w{3})\)$").unwrap();
 
-    let instructions = instructions.chars().map(Instruction::from_char).collect();
+    let (instructions, rest) = input.split_once("\n\n").ok_or_else(|| {
+        ParseError::at(
+            8,
+            input,
+            input,
+            "expected a blank line after the instructions",
+        )
+    })?;
 
-    let nodes = rest
+    let instructions = instructions
+        .char_indices()
+        .map(|(i, c)| match c {
+            'L' => Ok(Instruction::Left),
+            'R' => Ok(Instruction::Right),
+            _ => Err(ParseError::at(
+                8,
+                input,
+                &instructions[i..],
+                "unknown instruction",
+            )),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if instructions.is_empty() {
+        return Err(ParseError::new(8, 1, 1, "no instructions"));
+    }
+
+    let lines = rest
         .lines()
         .map(|line| {
-            let (source, left, right) = re
-                .find_iter(line)
-                .map(|m| m.as_str().to_owned())
-                .collect_tuple()
-                .unwrap();
+            re.captures(line)
+                .ok_or_else(|| ParseError::at(8, input, line, "expected `AAA = (BBB, CCC)`"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let nodes = lines
+        .iter()
+        .map(|captures| {
+            let [source, left, right] = [1, 2, 3].map(|i| captures[i].to_owned());
             (source, (left, right))
         })
         .collect::<NodeMap>();
+    for captures in &lines {
+        for target in [2, 3].map(|i| captures.get(i).unwrap().as_str()) {
+            if !nodes.contains_key(target) {
+                let message = format!("node `{target}` is never defined");
+                return Err(ParseError::at(8, input, target, message));
+            }
+        }
+    }
 
-    Input {
+    Ok(Input {
         instructions,
         nodes,
+    })
+}
+
+// the network, checked to have every node the part starts from
+fn parse_with_start(
+    input: &str,
+    is_start: impl Fn(&str) -> bool,
+    missing: &str,
+) -> Result<Input, ParseError> {
+    let network = parse_network(input)?;
+    if !network.nodes.keys().any(|node| is_start(node)) {
+        let (_, rest) = input.split_once("\n\n").unwrap();
+        return Err(ParseError::at(8, input, rest, missing));
     }
+    Ok(network)
+}
+
+#[aoc_generator(day8, part1)]
+fn parse_input1(input: &str) -> Result<Input, ParseError> {
+    parse_with_start(input, |node| node == "AAA", "no node `AAA` to start from")
+}
+
+#[aoc_generator(day8, part2)]
+fn parse_input2(input: &str) -> Result<Input, ParseError> {
+    parse_with_start(
+        input,
+        |node| node.ends_with('A'),
+        "no node ending in `A` to start from",
+    )
 }
 
 fn follow(start: &str, nodes: &NodeMap, instructions: &[Instruction]) -> u64 {
@@ -83,3 +160,81 @@ fn part2(input: &Input) -> u64 {
         .reduce(|acc, x| acc * x / binary_u64(acc, x))
         .unwrap()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE1: &str = "RL
+
+AAA = (BBB, CCC)
+BBB = (DDD, EEE)
+CCC = (ZZZ, GGG)
+DDD = (DDD, DDD)
+EEE = (EEE, EEE)
+GGG = (GGG, GGG)
+ZZZ = (ZZZ, ZZZ)";
+
+    const EXAMPLE2: &str = "LLR
+
+AAA = (BBB, BBB)
+BBB = (AAA, ZZZ)
+ZZZ = (ZZZ, ZZZ)";
+
+    const EXAMPLE3: &str = "LR
+
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+22A = (22B, XXX)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+XXX = (XXX, XXX)";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(part1(&parse_input1(EXAMPLE1).unwrap()), 2);
+        assert_eq!(part1(&parse_input1(EXAMPLE2).unwrap()), 6);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(part2(&parse_input2(EXAMPLE3).unwrap()), 6);
+    }
+
+    #[test]
+    fn reports_missing_nodes() {
+        assert_eq!(
+            parse_input1("L\n\nAAA = (BBB, CCC)").unwrap_err(),
+            ParseError::new(8, 3, 8, "node `BBB` is never defined")
+        );
+        assert_eq!(
+            parse_input1("L\n\nBBB = (BBB, ZZZ)\nZZZ = (ZZZ, ZZZ)").unwrap_err(),
+            ParseError::new(8, 3, 1, "no node `AAA` to start from")
+        );
+        assert_eq!(parse_input2(EXAMPLE1).map(|input| part2(&input)), Ok(2));
+        assert_eq!(
+            parse_input2("L\n\nZZZ = (ZZZ, ZZZ)").unwrap_err(),
+            ParseError::new(8, 3, 1, "no node ending in `A` to start from")
+        );
+    }
+
+    #[test]
+    fn dot_export() {
+        let dot = parse_network(EXAMPLE2).unwrap().to_dot();
+        assert_eq!(
+            dot,
+            "digraph {
+    \"AAA\" [label=\"AAA\", color=red, penwidth=2];
+    \"AAA\" -> \"BBB\" [label=\"LR\"];
+    \"BBB\" [label=\"BBB\"];
+    \"BBB\" -> \"AAA\" [label=\"L\"];
+    \"BBB\" -> \"ZZZ\" [label=\"R\"];
+    \"ZZZ\" [label=\"ZZZ\", color=red, penwidth=2];
+    \"ZZZ\" -> \"ZZZ\" [label=\"LR\"];
+}
+"
+        );
+    }
+}
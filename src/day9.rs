@@ -1,10 +1,12 @@
+use crate::util::error::*;
+
 #[aoc_generator(day9)]
-fn parse_input(input: &str) -> Vec<Vec<i64>> {
+fn parse_input(input: &str) -> Result<Vec<Vec<i64>>, ParseError> {
     input
         .lines()
         .map(|line| {
             line.split_ascii_whitespace()
-                .filter_map(|s| s.parse().ok())
+                .map(|s| parse_token(9, input, s))
                 .collect()
         })
         .collect()
@@ -31,3 +33,22 @@ fn part2(input: &[Vec<i64>]) -> i64 {
         .map(|nums| extrapolate(nums.iter().copied().rev().collect::<Vec<_>>().as_slice()))
         .sum()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "0 3 6 9 12 15
+1 3 6 10 15 21
+10 13 16 21 30 45";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(part1(&parse_input(EXAMPLE).unwrap()), 114);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(part2(&parse_input(EXAMPLE).unwrap()), 2);
+    }
+}
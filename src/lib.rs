@@ -1,9 +1,38 @@
 extern crate aoc_runner;
 #[macro_use]
 extern crate aoc_runner_derive;
-mod util {
+pub mod util {
+    pub mod dot;
+    pub mod error;
+    pub mod geometry;
     pub mod grid;
+    pub mod interval;
     pub mod point;
+    pub mod search;
+}
+/// Drawings of the puzzle input for looking at it outside the solutions, see
+/// `examples/render.rs`.
+pub mod render {
+    pub use crate::day17::render_route as day17_route;
+    pub use crate::day20::to_dot as day20_dot;
+    pub use crate::day23::to_dot as day23_dot;
+    pub use crate::day8::to_dot as day8_dot;
+}
+/// Questions about the puzzle input that neither part needs answered.
+pub mod query {
+    pub use crate::day17::route_with as day17_route_with;
+    pub use crate::day17::CrucibleRules as Day17CrucibleRules;
+    pub use crate::day17::Route as Day17Route;
+    pub use crate::day17::Step as Day17Step;
+    pub use crate::day17::Turns as Day17Turns;
+    pub use crate::day19::count_accepted as day19_count_accepted;
+    pub use crate::day20::network as day20_network;
+    pub use crate::day20::Network as Day20Network;
+    pub use crate::day21::reachable_after as day21_reachable_after;
+    pub use crate::day21::Tiling as Day21Tiling;
+    pub use crate::day5::map_entries as day5_map_entries;
+    pub use crate::day5::seeds_at as day5_seeds_at;
+    pub use crate::day5::MapEntry as Day5MapEntry;
 }
 mod day1;
 mod day10;
@@ -21,6 +50,8 @@ mod day20;
 mod day21;
 mod day22;
 mod day23;
+mod day24;
+mod day25;
 mod day3;
 mod day4;
 mod day5;
@@ -0,0 +1,91 @@
+use std::fmt::{Display, Write};
+
+/// Builds a Graphviz digraph, render it with e.g. `dot -Tsvg graph.dot -o graph.svg`.
+pub struct Dot {
+    out: String,
+}
+
+impl Default for Dot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn quote(text: impl Display) -> String {
+    format!(
+        "\"{}\"",
+        text.to_string().replace('\\', "\\\\").replace('"', "\\\"")
+    )
+}
+
+const HIGHLIGHT: &str = "color=red, penwidth=2";
+
+impl Dot {
+    pub fn new() -> Self {
+        Dot {
+            out: "digraph {\n".to_string(),
+        }
+    }
+
+    pub fn node(&mut self, id: impl Display, label: impl Display, highlight: bool) -> &mut Self {
+        let mut attrs = vec![format!("label={}", quote(label))];
+        if highlight {
+            attrs.push(HIGHLIGHT.to_string());
+        }
+        writeln!(self.out, "    {} [{}];", quote(id), attrs.join(", ")).unwrap();
+        self
+    }
+
+    /// An empty `label` leaves the edge unlabelled.
+    pub fn edge(
+        &mut self,
+        from: impl Display,
+        to: impl Display,
+        label: impl Display,
+        highlight: bool,
+    ) -> &mut Self {
+        let mut attrs = Vec::new();
+        let label = label.to_string();
+        if !label.is_empty() {
+            attrs.push(format!("label={}", quote(label)));
+        }
+        if highlight {
+            attrs.push(HIGHLIGHT.to_string());
+        }
+        write!(self.out, "    {} -> {}", quote(from), quote(to)).unwrap();
+        if !attrs.is_empty() {
+            write!(self.out, " [{}]", attrs.join(", ")).unwrap();
+        }
+        self.out.push_str(";\n");
+        self
+    }
+
+    pub fn finish(&mut self) -> String {
+        format!("{}}}\n", self.out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_digraph() {
+        let dot = Dot::new()
+            .node("a", "say \"hi\"", true)
+            .node("b", "b", false)
+            .edge("a", "b", 3, false)
+            .edge("b", "a", "", true)
+            .finish();
+        assert_eq!(
+            dot,
+            "digraph {
+    \"a\" [label=\"say \\\"hi\\\"\", color=red, penwidth=2];
+    \"b\" [label=\"b\"];
+    \"a\" -> \"b\" [label=\"3\"];
+    \"b\" -> \"a\" [color=red, penwidth=2];
+}
+"
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ParseError {
+    pub day: u8,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(day: u8, line: usize, column: usize, message: impl Into<String>) -> Self {
+        Self {
+            day,
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    /// Points at `token`, which must be a slice of `text`. Lines and columns are 1-based.
+    pub fn at(day: u8, text: &str, token: &str, message: impl Into<String>) -> Self {
+        let offset = (token.as_ptr() as usize)
+            .checked_sub(text.as_ptr() as usize)
+            .filter(|&offset| offset <= text.len())
+            .expect("token is not part of the parsed text");
+        let before = &text[..offset];
+        let line = before.matches('\n').count() + 1;
+        let column = offset - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+        Self::new(day, line, column, message)
+    }
+
+    /// For errors located within a single line or block that starts `lines` lines into the input.
+    pub fn offset_lines(mut self, lines: usize) -> Self {
+        self.line += lines;
+        self
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "day {} input, line {}, column {}: {}",
+            self.day, self.line, self.column, self.message
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+pub fn parse_token<T: FromStr>(day: u8, text: &str, token: &str) -> Result<T, ParseError> {
+    token
+        .parse()
+        .map_err(|_| ParseError::at(day, text, token, format!("invalid value `{token}`")))
+}
+
+/// Checks that `block` (a slice of `text`) is rectangular and only uses `allowed` characters.
+pub fn validate_grid(day: u8, text: &str, block: &str, allowed: &str) -> Result<(), ParseError> {
+    let width = block.lines().next().map_or(0, str::len);
+    if width == 0 {
+        return Err(ParseError::at(day, text, block, "empty grid"));
+    }
+    for line in block.lines() {
+        if let Some((i, c)) = line.char_indices().find(|&(_, c)| !allowed.contains(c)) {
+            let token = &line[i..i + c.len_utf8()];
+            return Err(ParseError::at(
+                day,
+                text,
+                token,
+                format!("unexpected `{c}`"),
+            ));
+        }
+        if line.len() != width {
+            let message = format!("expected {width} columns, found {}", line.len());
+            return Err(ParseError::at(day, text, line, message));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locates_token() {
+        let text = "first line\nsecond line";
+        let err = ParseError::at(1, text, &text[18..22], "bad");
+        assert_eq!(err, ParseError::new(1, 2, 8, "bad"));
+        assert_eq!(err.to_string(), "day 1 input, line 2, column 8: bad");
+    }
+
+    #[test]
+    fn locates_end_of_text() {
+        let text = "abc";
+        assert_eq!(ParseError::at(1, text, &text[3..], "eof").column, 4);
+    }
+
+    #[test]
+    fn parses_tokens() {
+        let text = "12 x4";
+        assert_eq!(parse_token::<u32>(1, text, &text[..2]), Ok(12));
+        assert_eq!(
+            parse_token::<u32>(1, text, &text[3..]),
+            Err(ParseError::new(1, 1, 4, "invalid value `x4`"))
+        );
+    }
+
+    #[test]
+    fn validates_grid() {
+        let text = "#.#\n..#\n#.";
+        assert_eq!(validate_grid(1, text, &text[..7], "#."), Ok(()));
+        assert_eq!(
+            validate_grid(1, text, text, "#."),
+            Err(ParseError::new(1, 3, 1, "expected 3 columns, found 2"))
+        );
+        assert_eq!(
+            validate_grid(1, text, text, "."),
+            Err(ParseError::new(1, 1, 1, "unexpected `#`"))
+        );
+    }
+}
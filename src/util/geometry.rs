@@ -0,0 +1,81 @@
+use crate::util::point::{Coord, Point};
+
+// consecutive vertices with the last one joined back to the first
+fn edges<T: Copy>(vertices: &[Point<T>]) -> impl Iterator<Item = (Point<T>, Point<T>)> + '_ {
+    vertices
+        .iter()
+        .copied()
+        .zip(vertices.iter().copied().cycle().skip(1))
+}
+
+fn twice_area<T: Coord>(vertices: &[Point<T>]) -> T {
+    edges(vertices)
+        .fold(T::zero(), |acc, (a, b)| acc + a.x * b.y - b.x * a.y)
+        .abs()
+}
+
+/// Area enclosed by the polygon through `vertices` in order, rounded down when it isn't
+/// a whole number, which only happens with diagonal edges.
+pub fn shoelace_area<T: Coord>(vertices: &[Point<T>]) -> T {
+    twice_area(vertices) / (T::one() + T::one())
+}
+
+/// Lattice points on the boundary of the polygon, which is its length when every edge
+/// is horizontal or vertical.
+pub fn perimeter<T: Coord>(vertices: &[Point<T>]) -> T {
+    edges(vertices).fold(T::zero(), |acc, (a, b)| {
+        acc + (b.x - a.x).abs().gcd(&(b.y - a.y).abs())
+    })
+}
+
+/// Lattice points strictly inside the polygon, by Pick's theorem.
+pub fn interior_lattice_points<T: Coord>(vertices: &[Point<T>]) -> T {
+    let two = T::one() + T::one();
+    (twice_area(vertices) - perimeter(vertices) + two) / two
+}
+
+/// Lattice points inside the polygon or on its boundary, e.g. the cells a closed trench
+/// on a grid digs out when it runs through the middle of its cells.
+pub fn boundary_plus_interior<T: Coord>(vertices: &[Point<T>]) -> T {
+    interior_lattice_points(vertices) + perimeter(vertices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(size: i32) -> [Point<i32>; 4] {
+        [
+            Point::new(0, 0),
+            Point::new(size, 0),
+            Point::new(size, size),
+            Point::new(0, size),
+        ]
+    }
+
+    #[test]
+    fn square_counts() {
+        let square = square(4);
+        assert_eq!(shoelace_area(&square), 16);
+        assert_eq!(perimeter(&square), 16);
+        assert_eq!(interior_lattice_points(&square), 9);
+        assert_eq!(boundary_plus_interior(&square), 25);
+    }
+
+    #[test]
+    fn orientation_and_diagonals() {
+        let mut reversed = square(3);
+        reversed.reverse();
+        assert_eq!(shoelace_area(&reversed), 9);
+
+        let triangle = [Point::new(0i64, 0), Point::new(4, 0), Point::new(0, 3)];
+        assert_eq!(shoelace_area(&triangle), 6);
+        // 4 + 3 along the axes and a single step along the hypotenuse
+        assert_eq!(perimeter(&triangle), 8);
+        assert_eq!(interior_lattice_points(&triangle), 3);
+
+        let half = [Point::new(0, 0), Point::new(1, 0), Point::new(0, 1)];
+        assert_eq!(shoelace_area(&half), 0);
+        assert_eq!(boundary_plus_interior(&half), 3);
+    }
+}
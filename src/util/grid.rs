@@ -1,7 +1,8 @@
 use crate::util::point::*;
+use std::fmt;
 use std::ops::{Index, IndexMut};
 
-#[derive(Clone, Eq, PartialEq, Hash)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub struct Grid<T> {
     pub width: i32,
     pub height: i32,
@@ -10,13 +11,161 @@ pub struct Grid<T> {
 
 impl Grid<u8> {
     pub fn parse(input: &str) -> Self {
+        Self::parse_with(input, |b| b)
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn parse_with(input: &str, f: impl FnMut(u8) -> T) -> Self {
         let lines: Vec<_> = input.lines().map(str::as_bytes).collect();
         Grid {
-            width: lines[0].len() as i32,
+            width: lines.first().map_or(0, |line| line.len()) as i32,
             height: lines.len() as i32,
-            bytes: lines.into_iter().flatten().copied().collect(),
+            bytes: lines.into_iter().flatten().copied().map(f).collect(),
+        }
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            bytes: self.bytes.iter().map(f).collect(),
         }
     }
+
+    #[inline]
+    pub fn contains(&self, point: Point) -> bool {
+        point.x >= 0 && point.x < self.width && point.y >= 0 && point.y < self.height
+    }
+
+    #[inline]
+    pub fn get(&self, point: Point) -> Option<&T> {
+        self.contains(point).then(|| &self[point])
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self, point: Point) -> Option<&mut T> {
+        self.contains(point).then(|| &mut self[point])
+    }
+
+    /// Every point in row-major order.
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| Point::new(x, y)))
+    }
+
+    pub fn iter_with_points(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.points().zip(&self.bytes)
+    }
+
+    /// Neighbours of `point` in the given directions that lie inside the grid,
+    /// e.g. `grid.neighbors(point, &ORTHOGONAL)`.
+    pub fn neighbors<'a>(
+        &'a self,
+        point: Point,
+        directions: &'a [Point],
+    ) -> impl Iterator<Item = Point> + 'a {
+        directions
+            .iter()
+            .map(move |&dir| point + dir)
+            .filter(|&next| self.contains(next))
+    }
+
+    pub fn row(&self, y: i32) -> &[T] {
+        let start = (y * self.width) as usize;
+        &self.bytes[start..start + self.width as usize]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.bytes.chunks(self.width.max(1) as usize)
+    }
+
+    pub fn column(&self, x: i32) -> impl Iterator<Item = &T> {
+        self.bytes
+            .iter()
+            .skip(x as usize)
+            .step_by(self.width.max(1) as usize)
+    }
+
+    /// Views the grid as repeating infinitely in every direction.
+    pub fn tiled(&self) -> Tiled<'_, T> {
+        Tiled { grid: self }
+    }
+}
+
+#[derive(Copy, Clone)]
+pub struct Tiled<'a, T> {
+    grid: &'a Grid<T>,
+}
+
+impl<T> Tiled<'_, T> {
+    /// The matching point inside the original grid.
+    #[inline]
+    pub fn wrap(&self, point: Point) -> Point {
+        Point::new(
+            point.x.rem_euclid(self.grid.width),
+            point.y.rem_euclid(self.grid.height),
+        )
+    }
+
+    /// Which copy of the grid the point lies in, the original being at the origin.
+    #[inline]
+    pub fn tile(&self, point: Point) -> Point {
+        Point::new(
+            point.x.div_euclid(self.grid.width),
+            point.y.div_euclid(self.grid.height),
+        )
+    }
+}
+
+impl<T> Index<Point> for Tiled<'_, T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, point: Point) -> &Self::Output {
+        &self.grid[self.wrap(point)]
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: i32, height: i32, fill: T) -> Self {
+        Grid {
+            width,
+            height,
+            bytes: vec![fill; (width * height) as usize],
+        }
+    }
+
+    fn remap(&self, width: i32, height: i32, source: impl Fn(Point) -> Point) -> Self {
+        let bytes = (0..height)
+            .flat_map(|y| (0..width).map(move |x| Point::new(x, y)))
+            .map(|point| self[source(point)].clone())
+            .collect();
+        Grid {
+            width,
+            height,
+            bytes,
+        }
+    }
+
+    pub fn transpose(&self) -> Self {
+        self.remap(self.height, self.width, |p| Point::new(p.y, p.x))
+    }
+
+    pub fn rotate_clockwise(&self) -> Self {
+        let height = self.height;
+        self.remap(self.height, self.width, |p| {
+            Point::new(p.y, height - 1 - p.x)
+        })
+    }
+
+    /// Mirrors the grid left to right.
+    pub fn flip(&self) -> Self {
+        let width = self.width;
+        self.remap(self.width, self.height, |p| {
+            Point::new(width - 1 - p.x, p.y)
+        })
+    }
 }
 
 impl<T: Copy + PartialEq> Grid<T> {
@@ -27,10 +176,6 @@ impl<T: Copy + PartialEq> Grid<T> {
             Point::new(x, y)
         })
     }
-    #[inline]
-    pub fn contains(&self, point: Point) -> bool {
-        point.x >= 0 && point.x < self.width && point.y >= 0 && point.y < self.height
-    }
 }
 
 impl<T> Index<Point> for Grid<T> {
@@ -48,3 +193,88 @@ impl<T> IndexMut<Point> for Grid<T> {
         &mut self.bytes[(self.width * point.y + point.x) as usize]
     }
 }
+
+impl fmt::Display for Grid<u8> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            writeln!(f, "{}", String::from_utf8_lossy(row))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "abc\ndef";
+
+    #[test]
+    fn parse_and_index() {
+        let grid = Grid::parse(SAMPLE);
+        assert_eq!((grid.width, grid.height), (3, 2));
+        assert_eq!(grid[Point::new(2, 1)], b'f');
+        assert_eq!(grid.get(Point::new(3, 0)), None);
+        assert_eq!(grid.get(Point::new(0, 1)), Some(&b'd'));
+        assert_eq!(grid.find(b'e'), Some(Point::new(1, 1)));
+    }
+
+    #[test]
+    fn parse_with_and_new() {
+        let grid = Grid::parse_with("12\n34", |b| u32::from(b - b'0'));
+        assert_eq!(grid.bytes, vec![1, 2, 3, 4]);
+        let grid = Grid::new(2, 3, '.');
+        assert_eq!(grid.bytes.len(), 6);
+        assert!(grid.points().all(|p| grid[p] == '.'));
+    }
+
+    #[test]
+    fn points_in_row_major_order() {
+        let grid = Grid::parse(SAMPLE);
+        let points = grid.points().collect::<Vec<_>>();
+        assert_eq!(points.len(), 6);
+        assert_eq!(points[0], Point::new(0, 0));
+        assert_eq!(points[3], Point::new(0, 1));
+        assert!(grid.iter_with_points().all(|(p, &b)| grid[p] == b));
+    }
+
+    #[test]
+    fn neighbors_stay_in_bounds() {
+        let grid = Grid::parse(SAMPLE);
+        let corner = grid.neighbors(ORIGIN, &ORTHOGONAL).collect::<Vec<_>>();
+        assert_eq!(corner, vec![DOWN, RIGHT]);
+        assert_eq!(grid.neighbors(Point::new(1, 0), &DIAGONAL).count(), 5);
+    }
+
+    #[test]
+    fn rows_and_columns() {
+        let grid = Grid::parse(SAMPLE);
+        assert_eq!(grid.row(1), b"def");
+        assert_eq!(grid.rows().count(), 2);
+        assert_eq!(grid.column(1).copied().collect::<Vec<_>>(), b"be");
+    }
+
+    #[test]
+    fn transformations() {
+        let grid = Grid::parse(SAMPLE);
+        assert_eq!(grid.transpose().to_string(), "ad\nbe\ncf\n");
+        assert_eq!(grid.rotate_clockwise().to_string(), "da\neb\nfc\n");
+        assert_eq!(grid.flip().to_string(), "cba\nfed\n");
+        let mut rotated = grid.clone();
+        for _ in 0..4 {
+            rotated = rotated.rotate_clockwise();
+        }
+        assert_eq!(rotated, grid);
+    }
+
+    #[test]
+    fn tiled_wraps_every_direction() {
+        let grid = Grid::parse(SAMPLE);
+        let tiled = grid.tiled();
+        assert_eq!(tiled[Point::new(3, 0)], b'a');
+        assert_eq!(tiled[Point::new(-1, -1)], b'f');
+        assert_eq!(tiled.wrap(Point::new(-4, 5)), Point::new(2, 1));
+        assert_eq!(tiled.tile(Point::new(-4, 5)), Point::new(-2, 2));
+        assert_eq!(tiled.tile(Point::new(2, 1)), ORIGIN);
+    }
+}
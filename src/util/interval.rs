@@ -0,0 +1,380 @@
+use std::ops::Range;
+
+/// Disjoint half-open intervals, kept sorted with touching intervals merged.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IntervalSet<T> {
+    ranges: Vec<Range<T>>,
+}
+
+impl<T> Default for IntervalSet<T> {
+    fn default() -> Self {
+        IntervalSet { ranges: Vec::new() }
+    }
+}
+
+impl<T: Copy + Ord> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn ranges(&self) -> &[Range<T>] {
+        &self.ranges
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub fn min(&self) -> Option<T> {
+        self.ranges.first().map(|range| range.start)
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        let index = self.ranges.partition_point(|range| range.end <= value);
+        self.ranges
+            .get(index)
+            .is_some_and(|range| range.contains(&value))
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        self.ranges.iter().chain(&other.ranges).cloned().collect()
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while let (Some(a), Some(b)) = (self.ranges.get(i), other.ranges.get(j)) {
+            let (start, end) = (a.start.max(b.start), a.end.min(b.end));
+            if start < end {
+                ranges.push(start..end);
+            }
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        IntervalSet { ranges }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+        let mut others = other.ranges.iter().peekable();
+        for range in &self.ranges {
+            let mut start = range.start;
+            // anything ending before this range can't affect later ones either
+            while others.next_if(|b| b.end <= start).is_some() {}
+            let mut cuts = others.clone();
+            while let Some(b) = cuts.next_if(|b| b.start < range.end) {
+                if start < b.start {
+                    ranges.push(start..b.start);
+                }
+                start = start.max(b.end);
+            }
+            if start < range.end {
+                ranges.push(start..range.end);
+            }
+        }
+        IntervalSet { ranges }
+    }
+}
+
+impl IntervalSet<u64> {
+    /// Number of values in the set.
+    pub fn len(&self) -> u64 {
+        self.ranges
+            .iter()
+            .map(|range| range.end - range.start)
+            .sum()
+    }
+}
+
+impl<T: Copy + Ord> FromIterator<Range<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        let mut sorted: Vec<_> = iter.into_iter().filter(|r| r.start < r.end).collect();
+        sorted.sort_by_key(|range| range.start);
+        let mut ranges: Vec<Range<T>> = Vec::with_capacity(sorted.len());
+        for range in sorted {
+            match ranges.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => ranges.push(range),
+            }
+        }
+        IntervalSet { ranges }
+    }
+}
+
+impl<T: Copy + Ord> From<Range<T>> for IntervalSet<T> {
+    fn from(range: Range<T>) -> Self {
+        [range].into_iter().collect()
+    }
+}
+
+// offsets span the whole of u64 either way, so they're kept wide enough to add two together
+fn add_offset(value: u64, offset: i128) -> u64 {
+    (i128::from(value) + offset) as u64
+}
+
+fn shift(range: &Range<u64>, offset: i128) -> Range<u64> {
+    add_offset(range.start, offset)..add_offset(range.end, offset)
+}
+
+/// A function on `u64` adding a fixed offset within each of its disjoint pieces and
+/// leaving every other value unchanged.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PiecewiseAffine {
+    // sorted by start
+    pieces: Vec<(Range<u64>, i128)>,
+}
+
+impl PiecewiseAffine {
+    pub fn identity() -> Self {
+        Self::default()
+    }
+
+    /// Where pieces overlap the one listed first wins.
+    pub fn from_first_match(pieces: impl IntoIterator<Item = (Range<u64>, i128)>) -> Self {
+        let mut covered = IntervalSet::new();
+        let mut result = Vec::new();
+        for (range, offset) in pieces {
+            let range = IntervalSet::from(range);
+            for part in range.difference(&covered).ranges() {
+                result.push((part.clone(), offset));
+            }
+            covered = covered.union(&range);
+        }
+        Self::from_disjoint(result)
+    }
+
+    fn from_disjoint(mut pieces: Vec<(Range<u64>, i128)>) -> Self {
+        pieces.retain(|(range, offset)| range.start < range.end && *offset != 0);
+        pieces.sort_by_key(|(range, _)| range.start);
+        // neighbouring pieces moving values the same way are a single piece
+        let mut merged: Vec<(Range<u64>, i128)> = Vec::with_capacity(pieces.len());
+        for (range, offset) in pieces {
+            match merged.last_mut() {
+                Some((last, last_offset)) if last.end == range.start && *last_offset == offset => {
+                    last.end = range.end;
+                }
+                _ => merged.push((range, offset)),
+            }
+        }
+        PiecewiseAffine { pieces: merged }
+    }
+
+    pub fn pieces(&self) -> &[(Range<u64>, i128)] {
+        &self.pieces
+    }
+
+    /// Every value some piece applies to.
+    pub fn domain(&self) -> IntervalSet<u64> {
+        self.pieces.iter().map(|(range, _)| range.clone()).collect()
+    }
+
+    pub fn apply(&self, value: u64) -> u64 {
+        let index = self.pieces.partition_point(|(range, _)| range.end <= value);
+        match self.pieces.get(index) {
+            Some((range, offset)) if range.contains(&value) => add_offset(value, *offset),
+            _ => value,
+        }
+    }
+
+    /// The image of every value in `set`.
+    pub fn apply_set(&self, set: &IntervalSet<u64>) -> IntervalSet<u64> {
+        let moved = self.pieces.iter().flat_map(|(range, offset)| {
+            set.intersection(&IntervalSet::from(range.clone()))
+                .ranges
+                .into_iter()
+                .map(move |part| shift(&part, *offset))
+        });
+        let unmoved = set.difference(&self.domain()).ranges;
+        moved.chain(unmoved).collect()
+    }
+
+    /// Sorted pieces covering `0..u64::MAX`, the gaps between pieces filled with offset zero.
+    /// `u64::MAX` itself is past the end of every piece, so it always maps to itself.
+    pub fn segments(&self) -> Vec<(Range<u64>, i128)> {
+        let mut segments = Vec::with_capacity(self.pieces.len() * 2 + 1);
+        let mut start = 0;
+        for (range, offset) in &self.pieces {
+            if start < range.start {
+                segments.push((start..range.start, 0));
+            }
+            segments.push((range.clone(), *offset));
+            start = range.end;
+        }
+        if start < u64::MAX {
+            segments.push((start..u64::MAX, 0));
+        }
+        segments
+    }
+
+    /// Every value mapped to `value`.
+    pub fn preimages(&self, value: u64) -> Vec<u64> {
+        self.segments()
+            .iter()
+            .map(|(range, offset)| (range, add_offset(value, -offset)))
+            .filter(|(range, source)| range.contains(source))
+            .map(|(_, source)| source)
+            .collect()
+    }
+
+    /// The inverse function, if no two values are mapped to the same place.
+    pub fn inverse(&self) -> Option<Self> {
+        let images: Vec<_> = self
+            .pieces
+            .iter()
+            .map(|(range, offset)| (shift(range, *offset), -offset))
+            .collect();
+        let image_set: IntervalSet<u64> = images.iter().map(|(range, _)| range.clone()).collect();
+        let total = self
+            .pieces
+            .iter()
+            .map(|(range, _)| range.end - range.start)
+            .sum();
+        // moved values must land on distinct places that no unmoved value already holds
+        let injective = image_set.len() == total && image_set.difference(&self.domain()).is_empty();
+        injective.then(|| Self::from_disjoint(images))
+    }
+
+    /// The lowest value `set` is mapped to, checking only where `set` or a piece starts.
+    pub fn min_over(&self, set: &IntervalSet<u64>) -> Option<u64> {
+        self.segments()
+            .iter()
+            .filter_map(|(range, offset)| {
+                let hit = set.intersection(&IntervalSet::from(range.clone()));
+                Some(add_offset(hit.min()?, *offset))
+            })
+            .min()
+    }
+
+    /// The function applying `self` and then `next`.
+    pub fn then(&self, next: &Self) -> Self {
+        let mut pieces = Vec::new();
+        // values moved by self, then possibly again by next
+        for (range, offset) in &self.pieces {
+            let image = IntervalSet::from(shift(range, *offset));
+            for (next_range, next_offset) in &next.pieces {
+                let hit = image.intersection(&IntervalSet::from(next_range.clone()));
+                for part in hit.ranges() {
+                    pieces.push((shift(part, -offset), offset + next_offset));
+                }
+            }
+            for part in image.difference(&next.domain()).ranges() {
+                pieces.push((shift(part, -offset), *offset));
+            }
+        }
+        // values self leaves alone go straight to next
+        let untouched = self.domain();
+        for (next_range, next_offset) in &next.pieces {
+            for part in IntervalSet::from(next_range.clone())
+                .difference(&untouched)
+                .ranges()
+            {
+                pieces.push((part.clone(), *next_offset));
+            }
+        }
+        Self::from_disjoint(pieces)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(ranges: &[Range<u64>]) -> IntervalSet<u64> {
+        ranges.iter().cloned().collect()
+    }
+
+    #[test]
+    fn normalizes() {
+        let s = set(&[5..7, 1..3, 3..4, 10..10, 6..9]);
+        assert_eq!(s.ranges(), [1..4, 5..9]);
+        assert_eq!(s.len(), 7);
+        assert!(s.contains(3) && !s.contains(4) && s.contains(8) && !s.contains(9));
+        assert_eq!(s.min(), Some(1));
+    }
+
+    #[test]
+    fn set_operations() {
+        let a = set(&[0..10, 20..30]);
+        let b = set(&[5..25, 28..40]);
+        assert_eq!(a.union(&b), IntervalSet::from(0..40));
+        assert_eq!(a.intersection(&b).ranges(), [5..10, 20..25, 28..30]);
+        assert_eq!(a.difference(&b).ranges(), [0..5, 25..28]);
+        assert_eq!(b.difference(&a).ranges(), [10..20, 30..40]);
+        assert!(a.difference(&a).is_empty());
+        assert_eq!(
+            IntervalSet::from(0..100)
+                .difference(&set(&[10..20, 30..40]))
+                .ranges(),
+            [0..10, 20..30, 40..100]
+        );
+    }
+
+    #[test]
+    fn piecewise_apply() {
+        let f = PiecewiseAffine::from_first_match([(98..100, -48), (50..98, 2), (0..60, 5)]);
+        assert_eq!(f.pieces(), [(0..50, 5), (50..98, 2), (98..100, -48)]);
+        assert_eq!(
+            [0, 49, 50, 97, 98, 99, 100].map(|x| f.apply(x)),
+            [5, 54, 52, 99, 50, 51, 100]
+        );
+        assert_eq!(f.apply_set(&(90..110).into()).ranges(), [50..52, 92..110]);
+    }
+
+    #[test]
+    fn composition() {
+        let f = PiecewiseAffine::from_first_match([(10..20, 100), (50..60, -40)]);
+        let g = PiecewiseAffine::from_first_match([(105..130, 1), (0..15, 7)]);
+        let h = f.then(&g);
+        for x in 0..200 {
+            assert_eq!(h.apply(x), g.apply(f.apply(x)), "at {x}");
+        }
+        assert_eq!(PiecewiseAffine::identity().then(&g), g);
+        assert_eq!(g.then(&PiecewiseAffine::identity()), g);
+
+        // each offset fits in i64, their sum doesn't
+        let half = 1 << 63;
+        let f = PiecewiseAffine::from_first_match([(0..1, half - 1)]);
+        let g = PiecewiseAffine::from_first_match([(half as u64 - 1..half as u64, half - 1)]);
+        assert_eq!(f.then(&g).apply(0), u64::MAX - 1);
+    }
+
+    #[test]
+    fn segments_preimages_and_inverse() {
+        let f = PiecewiseAffine::from_first_match([(10..20, 10), (20..30, -10)]);
+        assert_eq!(
+            f.segments(),
+            [(0..10, 0), (10..20, 10), (20..30, -10), (30..u64::MAX, 0)]
+        );
+        assert_eq!(f.preimages(25), [15]);
+        assert_eq!(f.preimages(5), [5]);
+        let inverse = f.inverse().unwrap();
+        for x in 0..50 {
+            assert_eq!(inverse.apply(f.apply(x)), x);
+        }
+
+        // 10..20 lands on top of the unmoved 40..50
+        let g = PiecewiseAffine::from_first_match([(10..20, 30)]);
+        assert_eq!(g.preimages(45), [15, 45]);
+        assert_eq!(g.preimages(15), []);
+        assert_eq!(g.inverse(), None);
+    }
+
+    #[test]
+    fn min_over_breakpoints() {
+        let f = PiecewiseAffine::from_first_match([(10..20, 100), (50..60, -45)]);
+        let s = set(&[0..5, 12..55]);
+        let brute = s
+            .ranges()
+            .iter()
+            .flat_map(|r| r.clone())
+            .map(|x| f.apply(x))
+            .min();
+        assert_eq!(f.min_over(&s), brute);
+        assert_eq!(f.min_over(&s), Some(0));
+        assert_eq!(f.min_over(&(12..55).into()), Some(5));
+        assert_eq!(f.min_over(&IntervalSet::new()), None);
+    }
+}
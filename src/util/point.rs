@@ -1,32 +1,79 @@
-use std::ops::{Add, AddAssign, Mul, MulAssign, Sub, SubAssign};
-
-pub const ORIGIN: Point = Point::new(0, 0);
-pub const UP: Point = Point::new(0, -1);
-pub const DOWN: Point = Point::new(0, 1);
-pub const LEFT: Point = Point::new(-1, 0);
-pub const RIGHT: Point = Point::new(1, 0);
-pub const ORTHOGONAL: [Point; 4] = [UP, DOWN, LEFT, RIGHT];
-pub const DIAGONAL: [Point; 8] = [
-    Point::new(-1, -1),
-    UP,
-    Point::new(1, -1),
-    LEFT,
-    RIGHT,
-    Point::new(-1, 1),
-    DOWN,
-    Point::new(1, 1),
-];
+use num::{Integer, PrimInt, Signed, Unsigned};
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
+
+pub const ORIGIN: Point = Point::ORIGIN;
+pub const UP: Point = Point::UP;
+pub const DOWN: Point = Point::DOWN;
+pub const LEFT: Point = Point::LEFT;
+pub const RIGHT: Point = Point::RIGHT;
+pub const ORTHOGONAL: [Point; 4] = Point::ORTHOGONAL;
+pub const DIAGONAL: [Point; 8] = Point::DIAGONAL;
+
+/// Signed integer usable as a coordinate. The constants let directions be spelled out at
+/// compile time, which the `num` traits can't do.
+pub trait Coord: PrimInt + Integer + Signed + Hash + Debug {
+    /// Same width without a sign, holding the distance between any two values.
+    type Unsigned: PrimInt + Unsigned + Debug;
+
+    const ZERO: Self;
+    const ONE: Self;
+    const MINUS_ONE: Self;
+
+    fn abs_diff(self, other: Self) -> Self::Unsigned;
+}
+
+macro_rules! coord {
+    ($($t:ty => $u:ty),*) => {
+        $(impl Coord for $t {
+            type Unsigned = $u;
+
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+            const MINUS_ONE: Self = -1;
+
+            #[inline]
+            fn abs_diff(self, other: Self) -> $u {
+                self.abs_diff(other)
+            }
+        })*
+    };
+}
+
+coord!(i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128, isize => usize);
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
-pub struct Point {
-    pub x: i32,
-    pub y: i32,
+pub struct Point<T = i32> {
+    pub x: T,
+    pub y: T,
 }
 
-impl Point {
+impl<T> Point<T> {
     #[inline]
-    pub const fn new(x: i32, y: i32) -> Self {
+    pub const fn new(x: T, y: T) -> Self {
         Self { x, y }
     }
+}
+
+impl<T: Coord> Point<T> {
+    pub const ORIGIN: Self = Self::new(T::ZERO, T::ZERO);
+    pub const UP: Self = Self::new(T::ZERO, T::MINUS_ONE);
+    pub const DOWN: Self = Self::new(T::ZERO, T::ONE);
+    pub const LEFT: Self = Self::new(T::MINUS_ONE, T::ZERO);
+    pub const RIGHT: Self = Self::new(T::ONE, T::ZERO);
+    pub const ORTHOGONAL: [Self; 4] = [Self::UP, Self::DOWN, Self::LEFT, Self::RIGHT];
+    pub const DIAGONAL: [Self; 8] = [
+        Self::new(T::MINUS_ONE, T::MINUS_ONE),
+        Self::UP,
+        Self::new(T::ONE, T::MINUS_ONE),
+        Self::LEFT,
+        Self::RIGHT,
+        Self::new(T::MINUS_ONE, T::ONE),
+        Self::DOWN,
+        Self::new(T::ONE, T::ONE),
+    ];
+
     #[inline]
     pub fn clockwise(self) -> Self {
         Point::new(-self.y, self.x)
@@ -35,41 +82,83 @@ impl Point {
     pub fn counter_clockwise(self) -> Self {
         Point::new(self.y, -self.x)
     }
+    /// Only overflows if the sum does, differences between any two coordinates fit.
     #[inline]
-    pub fn manhattan(self, other: Self) -> u32 {
+    pub fn manhattan(self, other: Self) -> T::Unsigned {
         self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
     }
+    #[inline]
+    pub fn chebyshev(self, other: Self) -> T::Unsigned {
+        self.x.abs_diff(other.x).max(self.y.abs_diff(other.y))
+    }
+    #[inline]
+    pub fn signum(self) -> Self {
+        Point::new(self.x.signum(), self.y.signum())
+    }
+    /// Z component of the cross product, positive when `other` is counter-clockwise of
+    /// `self` with y pointing up.
+    #[inline]
+    pub fn cross(self, other: Self) -> T {
+        self.x * other.y - self.y * other.x
+    }
+    /// Same point with another coordinate type, None if it doesn't fit.
+    #[inline]
+    pub fn cast<U: Coord>(self) -> Option<Point<U>> {
+        Some(Point::new(num::cast(self.x)?, num::cast(self.y)?))
+    }
+    /// Same point with a coordinate type that holds every value of this one.
+    #[inline]
+    pub fn widen<U: Coord + From<T>>(self) -> Point<U> {
+        Point::new(From::from(self.x), From::from(self.y))
+    }
 }
 
-impl From<u8> for Point {
+impl<T: Coord> From<u8> for Point<T> {
     #[inline]
     fn from(value: u8) -> Self {
         match value {
-            b'^' | b'U' => UP,
-            b'>' | b'R' => RIGHT,
-            b'v' | b'D' => DOWN,
-            b'<' | b'L' => LEFT,
+            b'^' | b'U' => Self::UP,
+            b'>' | b'R' => Self::RIGHT,
+            b'v' | b'D' => Self::DOWN,
+            b'<' | b'L' => Self::LEFT,
             _ => unreachable!("malformed input"),
         }
     }
 }
-impl Mul<i32> for Point {
+
+impl<T: Copy + Mul<Output = T>> Mul<T> for Point<T> {
     type Output = Self;
     #[inline]
-    fn mul(self, rhs: i32) -> Self::Output {
+    fn mul(self, rhs: T) -> Self::Output {
         Point::new(self.x * rhs, self.y * rhs)
     }
 }
 
-impl MulAssign<i32> for Point {
+impl<T: Copy + MulAssign> MulAssign<T> for Point<T> {
     #[inline]
-    fn mul_assign(&mut self, rhs: i32) {
+    fn mul_assign(&mut self, rhs: T) {
         self.x *= rhs;
         self.y *= rhs;
     }
 }
 
-impl Add for Point {
+impl<T: Copy + Div<Output = T>> Div<T> for Point<T> {
+    type Output = Self;
+    #[inline]
+    fn div(self, rhs: T) -> Self::Output {
+        Point::new(self.x / rhs, self.y / rhs)
+    }
+}
+
+impl<T: Neg<Output = T>> Neg for Point<T> {
+    type Output = Self;
+    #[inline]
+    fn neg(self) -> Self::Output {
+        Point::new(-self.x, -self.y)
+    }
+}
+
+impl<T: Add<Output = T>> Add for Point<T> {
     type Output = Self;
     #[inline]
     fn add(self, rhs: Self) -> Self::Output {
@@ -77,7 +166,7 @@ impl Add for Point {
     }
 }
 
-impl AddAssign for Point {
+impl<T: AddAssign> AddAssign for Point<T> {
     #[inline]
     fn add_assign(&mut self, rhs: Self) {
         self.x += rhs.x;
@@ -85,7 +174,7 @@ impl AddAssign for Point {
     }
 }
 
-impl Sub for Point {
+impl<T: Sub<Output = T>> Sub for Point<T> {
     type Output = Self;
     #[inline]
     fn sub(self, rhs: Self) -> Self::Output {
@@ -93,10 +182,201 @@ impl Sub for Point {
     }
 }
 
-impl SubAssign for Point {
+impl<T: SubAssign> SubAssign for Point<T> {
     #[inline]
     fn sub_assign(&mut self, rhs: Self) {
         self.x -= rhs.x;
         self.y -= rhs.y;
     }
 }
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Point3<T = i32> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+impl<T> Point3<T> {
+    #[inline]
+    pub const fn new(x: T, y: T, z: T) -> Self {
+        Self { x, y, z }
+    }
+    #[inline]
+    pub fn map<U>(self, mut f: impl FnMut(T) -> U) -> Point3<U> {
+        Point3::new(f(self.x), f(self.y), f(self.z))
+    }
+}
+
+// by reference and `Clone` rather than `Copy`, so big integers work too
+impl<T: Clone + Add<Output = T> + Sub<Output = T> + Mul<Output = T>> Point3<T> {
+    #[inline]
+    pub fn dot(&self, other: &Self) -> T {
+        self.x.clone() * other.x.clone()
+            + self.y.clone() * other.y.clone()
+            + self.z.clone() * other.z.clone()
+    }
+    #[inline]
+    pub fn cross(&self, other: &Self) -> Self {
+        let Point3 { x, y, z } = self.clone();
+        let Point3 {
+            x: ox,
+            y: oy,
+            z: oz,
+        } = other.clone();
+        Point3::new(
+            y.clone() * oz.clone() - z.clone() * oy.clone(),
+            z * ox.clone() - x.clone() * oz,
+            x * oy - y * ox,
+        )
+    }
+}
+
+impl<T: Coord> Point3<T> {
+    pub const ORIGIN: Self = Self::new(T::ZERO, T::ZERO, T::ZERO);
+
+    /// Projection onto the XY plane.
+    #[inline]
+    pub fn xy(self) -> Point<T> {
+        Point::new(self.x, self.y)
+    }
+    /// Only overflows if the sum does, differences between any two coordinates fit.
+    #[inline]
+    pub fn manhattan(self, other: Self) -> T::Unsigned {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y) + self.z.abs_diff(other.z)
+    }
+    #[inline]
+    pub fn chebyshev(self, other: Self) -> T::Unsigned {
+        self.x
+            .abs_diff(other.x)
+            .max(self.y.abs_diff(other.y))
+            .max(self.z.abs_diff(other.z))
+    }
+    #[inline]
+    pub fn signum(self) -> Self {
+        Point3::new(self.x.signum(), self.y.signum(), self.z.signum())
+    }
+    /// Same point with another coordinate type, None if it doesn't fit.
+    #[inline]
+    pub fn cast<U: Coord>(self) -> Option<Point3<U>> {
+        Some(Point3::new(
+            num::cast(self.x)?,
+            num::cast(self.y)?,
+            num::cast(self.z)?,
+        ))
+    }
+    /// Same point with a coordinate type that holds every value of this one.
+    #[inline]
+    pub fn widen<U: Coord + From<T>>(self) -> Point3<U> {
+        Point3::new(From::from(self.x), From::from(self.y), From::from(self.z))
+    }
+}
+
+impl<T> From<[T; 3]> for Point3<T> {
+    #[inline]
+    fn from([x, y, z]: [T; 3]) -> Self {
+        Point3::new(x, y, z)
+    }
+}
+
+impl<T> From<Point3<T>> for [T; 3] {
+    #[inline]
+    fn from(point: Point3<T>) -> Self {
+        [point.x, point.y, point.z]
+    }
+}
+
+impl<T: Copy + Mul<Output = T>> Mul<T> for Point3<T> {
+    type Output = Self;
+    #[inline]
+    fn mul(self, rhs: T) -> Self::Output {
+        Point3::new(self.x * rhs, self.y * rhs, self.z * rhs)
+    }
+}
+
+impl<T: Copy + Div<Output = T>> Div<T> for Point3<T> {
+    type Output = Self;
+    #[inline]
+    fn div(self, rhs: T) -> Self::Output {
+        Point3::new(self.x / rhs, self.y / rhs, self.z / rhs)
+    }
+}
+
+impl<T: Neg<Output = T>> Neg for Point3<T> {
+    type Output = Self;
+    #[inline]
+    fn neg(self) -> Self::Output {
+        Point3::new(-self.x, -self.y, -self.z)
+    }
+}
+
+impl<T: Add<Output = T>> Add for Point3<T> {
+    type Output = Self;
+    #[inline]
+    fn add(self, rhs: Self) -> Self::Output {
+        Point3::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Point3<T> {
+    type Output = Self;
+    #[inline]
+    fn sub(self, rhs: Self) -> Self::Output {
+        Point3::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constants_match_every_width() {
+        assert_eq!(Point::<i64>::UP, Point::new(0, -1));
+        assert_eq!(Point::<i8>::DIAGONAL[7], Point::new(1, 1));
+        assert_eq!(
+            ORTHOGONAL.map(|p| p.widen::<i64>()),
+            Point::<i64>::ORTHOGONAL
+        );
+        assert_eq!(Point::<i64>::from(b'<'), Point::LEFT);
+    }
+
+    #[test]
+    fn casts_check_for_overflow() {
+        let far = Point::new(3_000_000_000i64, -4);
+        assert_eq!(far.cast::<i32>(), None);
+        assert_eq!(far.cast::<i128>(), Some(Point::new(3_000_000_000, -4)));
+        assert_eq!(Point::new(-4i64, 7).cast::<i8>(), Some(Point::new(-4, 7)));
+        let far = Point3::new(0, 0, i64::MIN);
+        assert_eq!(far.cast::<i32>(), None);
+        assert_eq!(far.widen::<i128>().z, i128::from(i64::MIN));
+    }
+
+    #[test]
+    fn arithmetic() {
+        let p = Point::new(6, -3);
+        assert_eq!(-p, Point::new(-6, 3));
+        assert_eq!(p / 3, Point::new(2, -1));
+        assert_eq!(p.signum(), Point::new(1, -1));
+        assert_eq!(p.manhattan(ORIGIN), 9);
+        assert_eq!(p.chebyshev(Point::new(1, 1)), 5);
+        let (low, high) = (Point::new(i32::MIN, 0), Point::new(i32::MAX, 0));
+        assert_eq!(low.manhattan(high), u32::MAX);
+        assert_eq!(RIGHT.cross(DOWN), 1);
+        assert_eq!(UP.clockwise(), RIGHT);
+    }
+
+    #[test]
+    fn arithmetic_3d() {
+        let (a, b) = (Point3::new(1i64, 2, 3), Point3::new(-2, 0, 5));
+        assert_eq!(a + b, Point3::new(-1, 2, 8));
+        assert_eq!(a - b, Point3::new(3, 2, -2));
+        assert_eq!(a.dot(&b), 13);
+        assert_eq!(a.cross(&b), Point3::new(10, -11, 4));
+        assert_eq!(a.cross(&b).dot(&a), 0);
+        assert_eq!(a.manhattan(b), 7);
+        assert_eq!(a.chebyshev(b), 3);
+        assert_eq!((-a).signum(), Point3::new(-1, -1, -1));
+        assert_eq!(<[i64; 3]>::from(a * 2), [2, 4, 6]);
+    }
+}
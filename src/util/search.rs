@@ -0,0 +1,211 @@
+use fxhash::{FxHashMap, FxHashSet};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::hash::Hash;
+
+pub trait Graph {
+    type Node: Clone + Eq + Hash;
+
+    /// Neighbouring nodes along with the cost of moving to each of them.
+    fn successors(&self, node: &Self::Node) -> impl IntoIterator<Item = (Self::Node, u64)>;
+
+    /// Lower bound on the remaining cost to a goal, used by `astar`.
+    fn heuristic(&self, _node: &Self::Node) -> u64 {
+        0
+    }
+
+    fn is_goal(&self, _node: &Self::Node) -> bool {
+        false
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Path<N> {
+    pub cost: u64,
+    /// Every node from the start to the goal, both included.
+    pub nodes: Vec<N>,
+}
+
+struct Entry<N> {
+    priority: u64,
+    cost: u64,
+    node: N,
+}
+
+impl<N> PartialEq for Entry<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl<N> Eq for Entry<N> {}
+
+impl<N> PartialOrd for Entry<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N> Ord for Entry<N> {
+    // reversed so the max-heap pops the cheapest entry first
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.priority.cmp(&self.priority)
+    }
+}
+
+fn best_first<G: Graph>(
+    graph: &G,
+    start: G::Node,
+    heuristic: impl Fn(&G::Node) -> u64,
+) -> Option<Path<G::Node>> {
+    let mut costs = FxHashMap::default();
+    let mut prev: FxHashMap<G::Node, G::Node> = FxHashMap::default();
+    let mut queue = BinaryHeap::new();
+    costs.insert(start.clone(), 0);
+    queue.push(Entry {
+        priority: heuristic(&start),
+        cost: 0,
+        node: start,
+    });
+
+    while let Some(Entry { cost, node, .. }) = queue.pop() {
+        if costs.get(&node).is_some_and(|&best| cost > best) {
+            continue;
+        }
+        if graph.is_goal(&node) {
+            let mut nodes = vec![node];
+            while let Some(before) = prev.get(&nodes[nodes.len() - 1]) {
+                nodes.push(before.clone());
+            }
+            nodes.reverse();
+            return Some(Path { cost, nodes });
+        }
+        for (next, step) in graph.successors(&node) {
+            let next_cost = cost + step;
+            if costs.get(&next).is_none_or(|&best| next_cost < best) {
+                costs.insert(next.clone(), next_cost);
+                prev.insert(next.clone(), node.clone());
+                queue.push(Entry {
+                    priority: next_cost + heuristic(&next),
+                    cost: next_cost,
+                    node: next,
+                });
+            }
+        }
+    }
+    None
+}
+
+/// Cheapest path from `start` to any goal node.
+pub fn dijkstra<G: Graph>(graph: &G, start: G::Node) -> Option<Path<G::Node>> {
+    best_first(graph, start, |_| 0)
+}
+
+/// Cheapest path from `start` to any goal node, guided by an admissible `Graph::heuristic`.
+pub fn astar<G: Graph>(graph: &G, start: G::Node) -> Option<Path<G::Node>> {
+    best_first(graph, start, |node| graph.heuristic(node))
+}
+
+/// Nodes grouped by their unweighted distance from `start`, the first layer being `[start]`.
+/// Keeps going until every reachable node has been visited, so take only what is needed
+/// from infinite graphs.
+pub fn bfs_layers<G: Graph>(graph: &G, start: G::Node) -> impl Iterator<Item = Vec<G::Node>> + '_ {
+    let mut visited = FxHashSet::from_iter([start.clone()]);
+    let mut frontier = vec![start];
+    std::iter::from_fn(move || {
+        if frontier.is_empty() {
+            return None;
+        }
+        let mut next_frontier = Vec::new();
+        for node in &frontier {
+            for (next, _) in graph.successors(node) {
+                if visited.insert(next.clone()) {
+                    next_frontier.push(next);
+                }
+            }
+        }
+        Some(std::mem::replace(&mut frontier, next_frontier))
+    })
+}
+
+/// Unweighted distance to every node at most `max_steps` away from `start`.
+pub fn reachable_within<G: Graph>(
+    graph: &G,
+    start: G::Node,
+    max_steps: usize,
+) -> FxHashMap<G::Node, usize> {
+    bfs_layers(graph, start)
+        .take(max_steps + 1)
+        .enumerate()
+        .flat_map(|(steps, layer)| layer.into_iter().map(move |node| (node, steps)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::grid::*;
+    use crate::util::point::*;
+
+    // digits are the cost of entering a cell, `#` is a wall
+    struct Maze(Grid<u8>);
+
+    impl Graph for Maze {
+        type Node = Point;
+
+        fn successors(&self, &node: &Point) -> impl IntoIterator<Item = (Point, u64)> {
+            self.0
+                .neighbors(node, &ORTHOGONAL)
+                .filter(|&next| self.0[next] != b'#')
+                .map(|next| (next, u64::from(self.0[next] - b'0')))
+                .collect::<Vec<_>>()
+        }
+
+        fn heuristic(&self, &node: &Point) -> u64 {
+            u64::from(node.manhattan(Point::new(self.0.width - 1, self.0.height - 1)))
+        }
+
+        fn is_goal(&self, &node: &Point) -> bool {
+            node == Point::new(self.0.width - 1, self.0.height - 1)
+        }
+    }
+
+    const MAZE: &str = "\
+1911
+1#91
+1111";
+
+    #[test]
+    fn dijkstra_finds_cheapest_path() {
+        let path = dijkstra(&Maze(Grid::parse(MAZE)), ORIGIN).unwrap();
+        assert_eq!(path.cost, 5);
+        assert_eq!(path.nodes.len(), 6);
+        assert_eq!(path.nodes[0], ORIGIN);
+        assert_eq!(path.nodes[5], Point::new(3, 2));
+    }
+
+    #[test]
+    fn astar_matches_dijkstra() {
+        let maze = Maze(Grid::parse(MAZE));
+        assert_eq!(astar(&maze, ORIGIN), dijkstra(&maze, ORIGIN));
+    }
+
+    #[test]
+    fn unreachable_goal() {
+        let maze = Maze(Grid::parse("1#1\n1#1"));
+        assert_eq!(dijkstra(&maze, ORIGIN), None);
+    }
+
+    #[test]
+    fn layers_and_reach() {
+        let maze = Maze(Grid::parse(MAZE));
+        let layers = bfs_layers(&maze, ORIGIN).collect::<Vec<_>>();
+        assert_eq!(layers.len(), 6);
+        assert_eq!(layers[0], vec![ORIGIN]);
+        assert_eq!(layers.iter().map(Vec::len).sum::<usize>(), 11);
+
+        let reach = reachable_within(&maze, ORIGIN, 2);
+        assert_eq!(reach.len(), 5);
+        assert_eq!(reach[&Point::new(0, 2)], 2);
+    }
+}